@@ -45,6 +45,23 @@ pub enum OrderQuantity {
     Percentage100,
 }
 
+impl OrderQuantity {
+    // The quantity to trade given the maximum quantity available.
+    pub fn of(&self, max_qty: f64) -> f64 {
+        match self {
+            OrderQuantity::Exact(q) => *q,
+            OrderQuantity::PercentageAmount(q) => {
+                assert!(*q <= 100);
+                max_qty * (*q as f64 / 100.0)
+            }
+            OrderQuantity::Percentage100 => max_qty,
+            OrderQuantity::Percentage75 => max_qty * (3.0 / 4.0),
+            OrderQuantity::Percentage50 => max_qty * (1.0 / 2.0),
+            OrderQuantity::Percentage25 => max_qty * (1.0 / 4.0),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct OrderMsg {
     tp: TradingPair,
@@ -215,17 +232,7 @@ fn order_thread(
         let requested_qty = round::floor(
             if msg.position == PositionType::Long {
                 // What percentage of our spend assets do we want to use?
-                match msg.quantity {
-                    OrderQuantity::Exact(q) => q,
-                    OrderQuantity::PercentageAmount(q) => {
-                        assert!(q <= 100);
-                        max_qty * (q as f64 / 100.0)
-                    }
-                    OrderQuantity::Percentage100 => max_qty,
-                    OrderQuantity::Percentage75 => max_qty * (3.0 / 4.0),
-                    OrderQuantity::Percentage50 => max_qty * (1.0 / 2.0),
-                    OrderQuantity::Percentage25 => max_qty * (1.0 / 4.0),
-                }
+                msg.quantity.of(max_qty)
             } else {
                 // Always sell all.
                 // TODO: If we sell first then we'll ignore the percentage stuff, so our first
@@ -0,0 +1,166 @@
+// Average Directional Index (adx.rs), measures trend strength regardless of direction.
+// https://www.investopedia.com/terms/a/adx.asp
+
+#[derive(Debug, Clone)]
+pub struct Adx {
+    // Number of candles used for Wilder smoothing.
+    pub period: u16,
+
+    prev_high: Option<f64>,
+    prev_low: Option<f64>,
+    prev_close: Option<f64>,
+
+    // Wilder smoothed true range and directional movement.
+    tr_smoothed: f64,
+    plus_dm_smoothed: f64,
+    minus_dm_smoothed: f64,
+    num_dm: u16,
+
+    // Directional index values collected before the first ADX value.
+    dx_sum: f64,
+    num_dx: u16,
+
+    plus_di: Option<f64>,
+    minus_di: Option<f64>,
+    latest: Option<f64>,
+}
+
+impl Adx {
+    pub fn new(period: u16) -> Self {
+        Adx {
+            period,
+            prev_high: None,
+            prev_low: None,
+            prev_close: None,
+            tr_smoothed: 0.0,
+            plus_dm_smoothed: 0.0,
+            minus_dm_smoothed: 0.0,
+            num_dm: 0,
+            dx_sum: 0.0,
+            num_dx: 0,
+            plus_di: None,
+            minus_di: None,
+            latest: None,
+        }
+    }
+
+    // Current ADX value.
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Current positive directional indicator.
+    #[allow(dead_code)]
+    pub fn plus_di(&self) -> Option<f64> {
+        self.plus_di
+    }
+
+    // Current negative directional indicator.
+    #[allow(dead_code)]
+    pub fn minus_di(&self) -> Option<f64> {
+        self.minus_di
+    }
+
+    // Compute the latest ADX value from the candle's high, low and close.
+    pub fn compute(&mut self, high: f64, low: f64, close: f64) {
+        if self.period == 0 || !(high.is_finite() && low.is_finite() && close.is_finite()) {
+            return;
+        }
+
+        if let (Some(prev_high), Some(prev_low), Some(prev_close)) =
+            (self.prev_high, self.prev_low, self.prev_close)
+        {
+            let tr = (high - low)
+                .max((high - prev_close).abs())
+                .max((low - prev_close).abs());
+            let up_move = high - prev_high;
+            let down_move = prev_low - low;
+            let plus_dm = if up_move > down_move && up_move > 0.0 {
+                up_move
+            } else {
+                0.0
+            };
+            let minus_dm = if down_move > up_move && down_move > 0.0 {
+                down_move
+            } else {
+                0.0
+            };
+
+            let period = self.period as f64;
+            if self.num_dm < self.period {
+                // Seed the smoothed values with a plain sum.
+                self.tr_smoothed += tr;
+                self.plus_dm_smoothed += plus_dm;
+                self.minus_dm_smoothed += minus_dm;
+                self.num_dm += 1;
+            } else {
+                self.tr_smoothed = self.tr_smoothed - (self.tr_smoothed / period) + tr;
+                self.plus_dm_smoothed =
+                    self.plus_dm_smoothed - (self.plus_dm_smoothed / period) + plus_dm;
+                self.minus_dm_smoothed =
+                    self.minus_dm_smoothed - (self.minus_dm_smoothed / period) + minus_dm;
+            }
+
+            if self.num_dm == self.period {
+                self.update_dx(period);
+            }
+        }
+
+        self.prev_high = Some(high);
+        self.prev_low = Some(low);
+        self.prev_close = Some(close);
+    }
+
+    fn update_dx(&mut self, period: f64) {
+        let (plus_di, minus_di) = if self.tr_smoothed > 0.0 {
+            (
+                100.0 * self.plus_dm_smoothed / self.tr_smoothed,
+                100.0 * self.minus_dm_smoothed / self.tr_smoothed,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        self.plus_di = Some(plus_di);
+        self.minus_di = Some(minus_di);
+
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 {
+            100.0 * (plus_di - minus_di).abs() / di_sum
+        } else {
+            0.0
+        };
+
+        match self.latest {
+            Some(prev_adx) => {
+                self.latest = Some(((prev_adx * (period - 1.0)) + dx) / period);
+            }
+            None => {
+                // The first ADX value is the average of the first period DX values.
+                self.dx_sum += dx;
+                self.num_dx += 1;
+                if self.num_dx == self.period {
+                    self.latest = Some(self.dx_sum / period);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strong_trend() {
+        let mut adx = Adx::new(14);
+        for i in 0..27 {
+            let cp = 100.0 + i as f64;
+            adx.compute(cp + 0.5, cp - 0.5, cp);
+            assert!(adx.latest().is_none());
+        }
+
+        adx.compute(127.5, 126.5, 127.0);
+        assert!(adx.latest().unwrap() > 99.0);
+        assert!(adx.plus_di().unwrap() > adx.minus_di().unwrap());
+    }
+}
@@ -0,0 +1,112 @@
+// Bill Williams Alligator (alligator.rs), three smoothed MAs of the median price
+// shifted forward, and the Gator Oscillator which plots the gaps between them.
+// https://www.investopedia.com/articles/trading/072115/exploring-williams-alligator-indicator.asp
+use crate::ma;
+
+use ma::MAData;
+
+// (period, forward shift) of each line.
+static JAW: (u16, usize) = (13, 8);
+static TEETH: (u16, usize) = (8, 5);
+static LIPS: (u16, usize) = (5, 3);
+
+// Smoothed MA over 'period' candles, keeping enough history to read it 'shift'
+// candles back. A smoothed MA over n candles is an EMA over 2n - 1.
+fn smoothed_ma((period, shift): (u16, usize)) -> (MAData, usize) {
+    (MAData::with_history(2 * period - 1, shift + 1), shift)
+}
+
+#[derive(Debug, Clone)]
+pub struct Alligator {
+    jaw: (MAData, usize),
+    teeth: (MAData, usize),
+    lips: (MAData, usize),
+}
+
+impl Alligator {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Alligator {
+            jaw: smoothed_ma(JAW),
+            teeth: smoothed_ma(TEETH),
+            lips: smoothed_ma(LIPS),
+        }
+    }
+
+    // Current value of each line, which is its MA from 'shift' candles ago.
+    #[allow(dead_code)]
+    pub fn jaw(&self) -> Option<f64> {
+        self.jaw.0.nth_back(self.jaw.1)
+    }
+
+    #[allow(dead_code)]
+    pub fn teeth(&self) -> Option<f64> {
+        self.teeth.0.nth_back(self.teeth.1)
+    }
+
+    #[allow(dead_code)]
+    pub fn lips(&self) -> Option<f64> {
+        self.lips.0.nth_back(self.lips.1)
+    }
+
+    // Gator Oscillator upper bar, the gap between the jaw and teeth.
+    #[allow(dead_code)]
+    pub fn gator_upper(&self) -> Option<f64> {
+        Some((self.jaw()? - self.teeth()?).abs())
+    }
+
+    // Gator Oscillator lower bar, the gap between the teeth and lips, negated.
+    #[allow(dead_code)]
+    pub fn gator_lower(&self) -> Option<f64> {
+        Some(-(self.teeth()? - self.lips()?).abs())
+    }
+
+    // Compute the lines from the candle's high and low.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, high: f64, low: f64) {
+        if !high.is_finite() || !low.is_finite() {
+            return;
+        }
+
+        let median = (high + low) / 2.0;
+        for (ma, _) in [&mut self.jaw, &mut self.teeth, &mut self.lips].iter_mut() {
+            ma.compute(median, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strong_trend() {
+        let mut alligator = Alligator::new();
+        for _ in 0..40 {
+            alligator.compute(101.0, 99.0);
+        }
+        assert_eq!(alligator.gator_upper(), Some(0.0));
+        assert_eq!(alligator.gator_lower(), Some(-0.0));
+
+        let mut gaps = vec![];
+        for i in 1..40 {
+            let mid = 100.0 + i as f64;
+            alligator.compute(mid + 1.0, mid - 1.0);
+            gaps.push(alligator.gator_upper().unwrap());
+        }
+
+        // Faster lines lead in a rising market.
+        let (jaw, teeth, lips) = (
+            alligator.jaw().unwrap(),
+            alligator.teeth().unwrap(),
+            alligator.lips().unwrap(),
+        );
+        assert!(lips > teeth && teeth > jaw);
+        assert!((alligator.gator_upper().unwrap() - (teeth - jaw)).abs() < 1e-9);
+        assert!((alligator.gator_lower().unwrap() + (lips - teeth)).abs() < 1e-9);
+
+        // The lines spread apart as the trend runs.
+        assert!(gaps.windows(2).all(|w| w[1] >= w[0]));
+        assert!(gaps[gaps.len() - 1] > gaps[0]);
+    }
+}
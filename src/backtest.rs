@@ -0,0 +1,574 @@
+// Backtest (backtest.rs), replays historical candles through a tracker and
+// simulates the fills its signals would have received.
+use crate::candlestick;
+use crate::position;
+use crate::process_md;
+use crate::tradingpair;
+
+use candlestick::Candle;
+use position::PositionType;
+use process_md::{IndicatorSnapshot, MarketDataTracker};
+use tradingpair::TradingPair;
+
+use std::time::Duration;
+
+// Crypto trades around the clock, so a year is a full 365 days of candles.
+static SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+// How a signal turns into a fill. With no latency the fill is at the signal
+// candle's close, otherwise at the open 'latency_candles' later. Slippage moves
+// the fill price against us.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillModel {
+    pub slippage_bps: f64,
+    pub latency_candles: u16,
+}
+
+impl FillModel {
+    // Fills at the signal price.
+    #[allow(dead_code)]
+    pub fn ideal() -> Self {
+        FillModel {
+            slippage_bps: 0.0,
+            latency_candles: 0,
+        }
+    }
+
+    // Fill price for a signal at candle 'index', None if it would fill after the
+    // last candle.
+    fn fill_price(&self, candles: &[Candle], index: usize, side: PositionType) -> Option<f64> {
+        let price = if self.latency_candles == 0 {
+            candles.get(index)?.close
+        } else {
+            candles.get(index + self.latency_candles as usize)?.open
+        };
+
+        let slippage = price * self.slippage_bps / 10_000.0;
+        match side {
+            PositionType::Long => Some(price + slippage),
+            PositionType::Short => Some(price - slippage),
+            PositionType::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    // Candle the signal fired on.
+    pub signal_index: usize,
+    pub side: PositionType,
+    pub price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backtest {
+    pub fills: Vec<Fill>,
+    // Account value at each candle's close.
+    pub equity: Vec<f64>,
+    // Time between candles, needed to annualize the metrics.
+    pub interval: Option<Duration>,
+}
+
+impl Backtest {
+    // Fractional change in equity from each candle's close to the next.
+    #[allow(dead_code)]
+    pub fn returns(&self) -> Vec<f64> {
+        self.equity
+            .windows(2)
+            .map(|w| if w[0] != 0.0 { w[1] / w[0] - 1.0 } else { 0.0 })
+            .collect()
+    }
+
+    // Fractional change in equity from the first candle's close to the last.
+    #[allow(dead_code)]
+    pub fn total_return(&self) -> Option<f64> {
+        match (self.equity.first(), self.equity.last()) {
+            (Some(first), Some(last)) if *first != 0.0 => Some(last / first - 1.0),
+            _ => None,
+        }
+    }
+
+    // Sharpe and Sortino ratios of the per-candle returns.
+    #[allow(dead_code)]
+    pub fn sharpe(&self) -> Option<f64> {
+        sharpe_ratio(&self.returns())
+    }
+
+    #[allow(dead_code)]
+    pub fn sortino(&self) -> Option<f64> {
+        sortino_ratio(&self.returns())
+    }
+
+    // Number of candles in a year at the backtest's interval.
+    #[allow(dead_code)]
+    pub fn periods_per_year(&self) -> Option<f64> {
+        let interval = self.interval?.as_secs_f64();
+        if interval > 0.0 {
+            Some(SECONDS_PER_YEAR / interval)
+        } else {
+            None
+        }
+    }
+
+    // Total return compounded out to a year.
+    #[allow(dead_code)]
+    pub fn annualized_return(&self) -> Option<f64> {
+        let periods = self.equity.len().checked_sub(1).filter(|n| *n > 0)?;
+        let growth = 1.0 + self.total_return()?;
+        Some(growth.powf(self.periods_per_year()? / periods as f64) - 1.0)
+    }
+
+    // Sample standard deviation of the per-candle returns scaled to a year.
+    #[allow(dead_code)]
+    pub fn annualized_volatility(&self) -> Option<f64> {
+        Some(std_dev(&self.returns())? * self.periods_per_year()?.sqrt())
+    }
+
+    #[allow(dead_code)]
+    pub fn annualized_sharpe(&self) -> Option<f64> {
+        Some(self.sharpe()? * self.periods_per_year()?.sqrt())
+    }
+}
+
+fn mean(returns: &[f64]) -> f64 {
+    returns.iter().sum::<f64>() / returns.len() as f64
+}
+
+// Sample standard deviation of the returns, None with fewer than two returns.
+fn std_dev(returns: &[f64]) -> Option<f64> {
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mean = mean(returns);
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+// Mean return over the sample standard deviation of the returns, None with fewer
+// than two returns or no variation.
+pub fn sharpe_ratio(returns: &[f64]) -> Option<f64> {
+    match std_dev(returns)? {
+        std_dev if std_dev > 0.0 => Some(mean(returns) / std_dev),
+        _ => None,
+    }
+}
+
+// Mean return over the downside deviation, the root mean square of the returns
+// below zero, so only losses count as risk. None with no returns or no losses.
+pub fn sortino_ratio(returns: &[f64]) -> Option<f64> {
+    if returns.is_empty() {
+        return None;
+    }
+
+    let downside = returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len() as f64;
+    if downside > 0.0 {
+        Some(mean(returns) / downside.sqrt())
+    } else {
+        None
+    }
+}
+
+// Feed the candles through the tracker, returning its signal at each candle.
+#[allow(dead_code)]
+pub fn signals(
+    mt: &mut MarketDataTracker,
+    tp: &TradingPair,
+    candles: &[Candle],
+) -> Vec<PositionType> {
+    candles
+        .iter()
+        .map(|candle| {
+            mt.on_candle(candle);
+            mt.signal(tp, candle.close)
+        })
+        .collect()
+}
+
+// Signals from a replay, and when recorded the indicators after each candle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub signals: Vec<PositionType>,
+    pub snapshots: Vec<IndicatorSnapshot>,
+}
+
+// As signals(), with 'record_history' also capturing a snapshot of the
+// indicators at every candle.
+#[allow(dead_code)]
+pub fn replay(
+    mt: &mut MarketDataTracker,
+    tp: &TradingPair,
+    candles: &[Candle],
+    record_history: bool,
+) -> Replay {
+    let mut replay = Replay {
+        signals: Vec::with_capacity(candles.len()),
+        snapshots: Vec::new(),
+    };
+
+    for candle in candles {
+        mt.on_candle(candle);
+        replay.signals.push(mt.signal(tp, candle.close));
+        if record_history {
+            replay.snapshots.push(mt.snapshot());
+        }
+    }
+
+    replay
+}
+
+// Lazily feed the candles through the tracker, yielding each with its signal.
+#[allow(dead_code)]
+pub fn signal_stream<'a, I>(
+    candles: I,
+    mut mt: MarketDataTracker,
+    tp: &'a TradingPair,
+) -> impl Iterator<Item = (Candle, PositionType)> + 'a
+where
+    I: IntoIterator<Item = Candle>,
+    I::IntoIter: 'a,
+{
+    candles.into_iter().map(move |candle| {
+        mt.on_candle(&candle);
+        let signal = mt.signal(tp, candle.close);
+        (candle, signal)
+    })
+}
+
+// Simulate trading the signals, flipping fully into a long or short position on
+// each change of direction, starting with 'starting_equity' in cash.
+#[allow(dead_code)]
+pub fn simulate(
+    candles: &[Candle],
+    signals: &[PositionType],
+    fill_model: &FillModel,
+    starting_equity: f64,
+) -> Backtest {
+    let mut fills = Vec::new();
+    let mut equity = Vec::with_capacity(candles.len());
+
+    let mut cash = starting_equity;
+    let mut qty = 0.0;
+    let mut position = PositionType::None;
+    // Signals waiting on latency, (signal index, side).
+    let mut pending: Vec<(usize, PositionType)> = Vec::new();
+
+    for (i, candle) in candles.iter().enumerate() {
+        if let Some(side) = signals.get(i) {
+            if *side != PositionType::None {
+                pending.push((i, *side));
+            }
+        }
+
+        while let Some((signal_index, side)) = pending.first().copied() {
+            if signal_index + fill_model.latency_candles as usize > i {
+                break;
+            }
+            pending.remove(0);
+
+            if side == position {
+                continue;
+            }
+            let price = match fill_model.fill_price(candles, signal_index, side) {
+                Some(price) => price,
+                None => continue,
+            };
+
+            // Close out what we hold, then put the whole account the other way.
+            cash += qty * price;
+            qty = if side == PositionType::Long {
+                cash / price
+            } else {
+                -cash / price
+            };
+            cash -= qty * price;
+            position = side;
+
+            fills.push(Fill {
+                signal_index,
+                side,
+                price,
+            });
+        }
+
+        equity.push(cash + qty * candle.close);
+    }
+
+    Backtest {
+        fills,
+        equity,
+        interval: candlestick::infer_interval(candles),
+    }
+}
+
+// Backtest the tracker 'build' returns for each set of parameters over the same
+// candles, best total return first.
+#[allow(dead_code)]
+pub fn sweep<P, F>(
+    candles: &[Candle],
+    tp: &TradingPair,
+    params: &[P],
+    fill_model: &FillModel,
+    starting_equity: f64,
+    build: F,
+) -> Vec<(P, Backtest)>
+where
+    P: Clone,
+    F: Fn(&P) -> MarketDataTracker,
+{
+    let mut results: Vec<(P, Backtest)> = params
+        .iter()
+        .map(|p| {
+            let mut mt = build(p);
+            let signals = signals(&mut mt, tp, candles);
+            (
+                p.clone(),
+                simulate(candles, &signals, fill_model, starting_equity),
+            )
+        })
+        .collect();
+
+    let total_return = |backtest: &Backtest| backtest.total_return().unwrap_or(f64::NEG_INFINITY);
+    results.sort_by(|(_, a), (_, b)| {
+        total_return(b)
+            .partial_cmp(&total_return(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use process_md::TradeSignal;
+
+    fn candle(open: f64, close: f64) -> Candle {
+        Candle {
+            open_time: 0,
+            close_time: 0,
+            open,
+            high: open.max(close),
+            low: open.min(close),
+            close,
+            volume: 0.0,
+            symbol: None,
+        }
+    }
+
+    #[test]
+    fn fill_model() {
+        // Up for six candles then down, opening at the previous close.
+        let closes = [
+            100.0, 101.0, 102.0, 103.0, 104.0, 105.0, 106.0, 105.0, 104.0, 103.0,
+        ];
+        let candles: Vec<Candle> = closes
+            .iter()
+            .enumerate()
+            .map(|(i, cp)| candle(if i > 0 { closes[i - 1] } else { *cp }, *cp))
+            .collect();
+        let mut signals = vec![PositionType::None; 10];
+        signals[1] = PositionType::Long;
+        signals[6] = PositionType::Short;
+
+        let ideal = simulate(&candles, &signals, &FillModel::ideal(), 1000.0);
+        assert_eq!(ideal.fills.len(), 2);
+        assert_eq!(ideal.fills[0].price, 101.0);
+        assert_eq!(ideal.fills[1].price, 106.0);
+
+        let model = FillModel {
+            slippage_bps: 10.0,
+            latency_candles: 1,
+        };
+        let real = simulate(&candles, &signals, &model, 1000.0);
+        assert_eq!(real.fills.len(), 2);
+        assert!((real.fills[0].price - 101.0 * 1.001).abs() < 1e-9);
+        assert!((real.fills[1].price - 106.0 * 0.999).abs() < 1e-9);
+
+        // Worse fills on the same signals.
+        assert_eq!(ideal.equity.len(), real.equity.len());
+        assert!(real.equity[9] < ideal.equity[9]);
+        // Nothing held until the delayed fill.
+        assert_eq!(real.equity[1], 1000.0);
+    }
+
+    #[test]
+    fn risk_adjusted_returns() {
+        let returns = [0.1, -0.05, 0.02, -0.01];
+        assert!((sharpe_ratio(&returns).unwrap() - 0.236189).abs() < 1e-6);
+        assert!((sortino_ratio(&returns).unwrap() - 0.588348).abs() < 1e-6);
+
+        assert_eq!(sharpe_ratio(&[0.01]), None);
+        assert_eq!(sharpe_ratio(&[0.01, 0.01]), None);
+        assert_eq!(sortino_ratio(&[0.01, 0.02]), None);
+
+        let backtest = Backtest {
+            fills: vec![],
+            equity: vec![100.0, 110.0, 104.5, 106.59, 105.5241],
+            interval: None,
+        };
+        let from_equity = backtest.returns();
+        for (r, expected) in from_equity.iter().zip(returns.iter()) {
+            assert!((r - expected).abs() < 1e-9);
+        }
+        assert!((backtest.sharpe().unwrap() - 0.236189).abs() < 1e-6);
+        assert!((backtest.sortino().unwrap() - 0.588348).abs() < 1e-6);
+    }
+
+    #[test]
+    fn annualized() {
+        let equity = vec![100.0, 110.0, 104.5, 106.59, 105.5241];
+        let daily = Backtest {
+            fills: vec![],
+            equity: equity.clone(),
+            interval: Some(Duration::from_secs(24 * 60 * 60)),
+        };
+        let hourly = Backtest {
+            interval: Some(Duration::from_secs(60 * 60)),
+            ..daily.clone()
+        };
+
+        assert_eq!(daily.periods_per_year(), Some(365.0));
+        assert_eq!(hourly.periods_per_year(), Some(8760.0));
+
+        // Four returns growing the account 5.5241%, compounded over a year.
+        let growth: f64 = 1.055241;
+        let expected = growth.powf(365.0 / 4.0) - 1.0;
+        assert!((daily.annualized_return().unwrap() - expected).abs() < 1e-9);
+        let expected = growth.powf(8760.0 / 4.0) - 1.0;
+        assert!((hourly.annualized_return().unwrap() / expected - 1.0).abs() < 1e-9);
+
+        let ratio =
+            hourly.annualized_volatility().unwrap() / daily.annualized_volatility().unwrap();
+        assert!((ratio - 24f64.sqrt()).abs() < 1e-9);
+        assert!((daily.annualized_sharpe().unwrap() - 0.236189 * 365f64.sqrt()).abs() < 1e-4);
+        let ratio = hourly.annualized_sharpe().unwrap() / daily.annualized_sharpe().unwrap();
+        assert!((ratio - 24f64.sqrt()).abs() < 1e-9);
+
+        // Simulating takes the interval from the candles.
+        let candles: Vec<Candle> = (0..3)
+            .map(|i| Candle {
+                open_time: i * 60_000,
+                close_time: i * 60_000 + 59_999,
+                ..candle(100.0, 100.0)
+            })
+            .collect();
+        let backtest = simulate(&candles, &[], &FillModel::ideal(), 100.0);
+        assert_eq!(backtest.interval, Some(Duration::from_secs(60)));
+        assert_eq!(backtest.annualized_return(), Some(0.0));
+
+        let unknown = Backtest {
+            interval: None,
+            ..daily
+        };
+        assert_eq!(unknown.annualized_return(), None);
+        assert_eq!(unknown.annualized_volatility(), None);
+    }
+
+    #[test]
+    fn fill_after_last_candle() {
+        let candles: Vec<Candle> = (0..3).map(|i| candle(100.0 + i as f64, 100.0)).collect();
+        let signals = vec![PositionType::None, PositionType::None, PositionType::Long];
+        let model = FillModel {
+            slippage_bps: 0.0,
+            latency_candles: 1,
+        };
+
+        let backtest = simulate(&candles, &signals, &model, 1000.0);
+        assert!(backtest.fills.is_empty());
+        assert_eq!(backtest.equity, vec![1000.0; 3]);
+    }
+
+    #[test]
+    fn replay_history() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let candles: Vec<Candle> = [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0]
+            .iter()
+            .map(|cp| candle(*cp, *cp))
+            .collect();
+
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        let replayed = replay(&mut mt, &tp, &candles, true);
+        assert_eq!(replayed.snapshots.len(), candles.len());
+        assert_eq!(replayed.signals[6], PositionType::Long);
+
+        // The signal candle is the last, so the live tracker still shows it.
+        let at_signal = &replayed.snapshots[6];
+        assert_eq!(*at_signal, mt.snapshot());
+        assert_eq!(at_signal.fast_ma, mt.fast_ma_data.latest());
+        assert_eq!(at_signal.slow_ma, mt.slow_ma_data.latest());
+        assert_eq!(at_signal.close, Some(13.0));
+        assert_eq!(at_signal.desired_position, PositionType::Long);
+        assert_eq!(replayed.snapshots[5].desired_position, PositionType::None);
+
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        let unrecorded = replay(&mut mt, &tp, &candles, false);
+        assert!(unrecorded.snapshots.is_empty());
+        assert_eq!(unrecorded.signals, replayed.signals);
+    }
+
+    #[test]
+    fn sweep() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let candles: Vec<Candle> = (0..80)
+            .map(|i| {
+                let cp = 100.0 + 10.0 * (i as f64 / 6.0).sin();
+                candle(cp, cp)
+            })
+            .collect();
+        let grid = [(2, 4), (3, 6), (2, 8), (4, 12)];
+
+        let results = super::sweep(&candles, &tp, &grid, &FillModel::ideal(), 1000.0, |p| {
+            MarketDataTracker::new(TradeSignal::MaCross, Some(p.1), Some(p.0), false)
+        });
+        assert_eq!(results.len(), grid.len());
+        for p in grid.iter() {
+            assert!(results.iter().any(|(params, _)| params == p));
+        }
+        for (_, backtest) in results.iter() {
+            assert_eq!(backtest.equity.len(), candles.len());
+        }
+        assert!(results
+            .iter()
+            .any(|(_, backtest)| !backtest.fills.is_empty()));
+        assert!(results
+            .windows(2)
+            .all(|w| w[0].1.total_return() >= w[1].1.total_return()));
+    }
+
+    #[test]
+    fn signal_stream() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let candles: Vec<Candle> = [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0, 14.0, 9.0, 5.0, 4.0]
+            .iter()
+            .map(|cp| candle(*cp, *cp))
+            .collect();
+
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        let expected = signals(&mut mt, &tp, &candles);
+        assert!(expected.contains(&PositionType::Long));
+
+        let mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        let streamed: Vec<(Candle, PositionType)> =
+            super::signal_stream(candles.clone(), mt, &tp).collect();
+        assert_eq!(streamed.len(), candles.len());
+        for (i, (candle, signal)) in streamed.iter().enumerate() {
+            assert_eq!(*candle, candles[i]);
+            assert_eq!(*signal, expected[i]);
+        }
+
+        // Composes with the usual adapters.
+        let mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        let entries = super::signal_stream(candles.clone(), mt, &tp)
+            .filter(|(_, signal)| *signal != PositionType::None)
+            .count();
+        assert_eq!(
+            entries,
+            expected
+                .iter()
+                .filter(|s| **s != PositionType::None)
+                .count()
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// Bollinger bands (bollinger.rs), an SMA with bands a number of standard deviations
+// either side of it.
+// https://www.investopedia.com/terms/b/bollingerbands.asp
+use crate::ma;
+
+use ma::MAData;
+
+#[derive(Debug)]
+pub struct BollingerBands {
+    pub ma: MAData,
+    // Number of standard deviations between the middle and outer bands.
+    pub num_std_dev: f64,
+    // Close price of the last candle computed.
+    close: Option<f64>,
+}
+
+impl BollingerBands {
+    #[allow(dead_code)]
+    pub fn new(num_candles: u16, num_std_dev: f64) -> Self {
+        BollingerBands {
+            ma: MAData::new(num_candles),
+            num_std_dev,
+            close: None,
+        }
+    }
+
+    // Middle band, the SMA of the close.
+    #[allow(dead_code)]
+    pub fn middle(&self) -> Option<f64> {
+        self.ma.latest()
+    }
+
+    // Upper band.
+    #[allow(dead_code)]
+    pub fn upper(&self) -> Option<f64> {
+        Some(self.ma.latest()? + (self.num_std_dev * self.ma.std_dev()?))
+    }
+
+    // Lower band.
+    #[allow(dead_code)]
+    pub fn lower(&self) -> Option<f64> {
+        Some(self.ma.latest()? - (self.num_std_dev * self.ma.std_dev()?))
+    }
+
+    // Where the close sits within the bands, 0 at the lower band and 1 at the upper.
+    // None if the bands have no width.
+    #[allow(dead_code)]
+    pub fn percent_b(&self) -> Option<f64> {
+        let (upper, lower) = (self.upper()?, self.lower()?);
+        if upper == lower {
+            return None;
+        }
+
+        Some((self.close? - lower) / (upper - lower))
+    }
+
+    // Width of the bands relative to the middle band, low values indicate a squeeze.
+    #[allow(dead_code)]
+    pub fn bandwidth(&self) -> Option<f64> {
+        let middle = self.middle()?;
+        if middle == 0.0 {
+            return None;
+        }
+
+        Some((self.upper()? - self.lower()?) / middle)
+    }
+
+    // Compute the latest bands based on the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        self.ma.compute(close_price, false);
+        self.close = Some(close_price);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_b_at_upper_band() {
+        // Mean of [1, 3] is 2 with a standard deviation of 1, so the upper band is 3.
+        let mut bb = BollingerBands::new(2, 1.0);
+        bb.compute(1.0);
+        assert!(bb.percent_b().is_none());
+
+        bb.compute(3.0);
+        assert_eq!(bb.upper(), Some(3.0));
+        assert_eq!(bb.lower(), Some(1.0));
+        assert_eq!(bb.percent_b(), Some(1.0));
+        assert_eq!(bb.bandwidth(), Some(1.0));
+    }
+
+    #[test]
+    fn squeeze() {
+        let mut wide = BollingerBands::new(20, 2.0);
+        let mut squeeze = BollingerBands::new(20, 2.0);
+        for i in 0..20 {
+            let offset = if i % 2 == 0 { 1.0 } else { -1.0 };
+            wide.compute(100.0 + 5.0 * offset);
+            squeeze.compute(100.0 + 0.01 * offset);
+        }
+
+        assert!(squeeze.bandwidth().unwrap() < 0.001);
+        assert!(squeeze.bandwidth().unwrap() < wide.bandwidth().unwrap());
+    }
+
+    #[test]
+    fn zero_width() {
+        let mut bb = BollingerBands::new(3, 2.0);
+        for _ in 0..3 {
+            bb.compute(10.0);
+        }
+
+        assert!(bb.percent_b().is_none());
+        assert_eq!(bb.bandwidth(), Some(0.0));
+    }
+}
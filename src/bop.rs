@@ -0,0 +1,79 @@
+// Balance of power (bop.rs), where the close sits against the open relative to the
+// candle's range. -1..1, positive when buyers dominated the candle.
+use crate::ma;
+
+use ma::MAData;
+
+#[derive(Debug)]
+pub struct Bop {
+    // Optional SMA over the raw values, used when over more than one candle.
+    ma: MAData,
+    latest: Option<f64>,
+}
+
+impl Bop {
+    // BOP smoothed by an SMA over 'smoothing' candles, 0 or 1 for the raw value.
+    #[allow(dead_code)]
+    pub fn new(smoothing: u16) -> Self {
+        Bop {
+            ma: MAData::new(if smoothing > 1 { smoothing } else { 0 }),
+            latest: None,
+        }
+    }
+
+    // Current balance of power.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Compute the latest value from the candle's prices.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, open: f64, high: f64, low: f64, close: f64) {
+        if ![open, high, low, close].iter().all(|v| v.is_finite()) {
+            return;
+        }
+
+        // A candle with no range shows no dominance either way.
+        let bop = if high > low {
+            (close - open) / (high - low)
+        } else {
+            0.0
+        };
+
+        if self.ma.num_candles == 0 {
+            self.latest = Some(bop);
+        } else {
+            self.ma.compute(bop, false);
+            self.latest = self.ma.latest();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bop() {
+        let mut bop = Bop::new(1);
+        bop.compute(10.0, 12.0, 9.9, 12.0);
+        assert!(bop.latest().unwrap() > 0.95);
+
+        bop.compute(12.0, 12.1, 10.0, 10.0);
+        assert!(bop.latest().unwrap() < -0.95);
+
+        bop.compute(10.0, 10.0, 10.0, 10.0);
+        assert_eq!(bop.latest(), Some(0.0));
+    }
+
+    #[test]
+    fn smoothed() {
+        let mut bop = Bop::new(2);
+        bop.compute(10.0, 12.0, 10.0, 12.0);
+        assert!(bop.latest().is_none());
+
+        bop.compute(12.0, 12.0, 10.0, 10.0);
+        assert_eq!(bop.latest(), Some(0.0));
+    }
+}
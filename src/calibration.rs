@@ -0,0 +1,172 @@
+// Indicator calibration (calibration.rs), reference input series with known
+// outputs the indicators are checked against, guarding the MA math against
+// regressions.
+//
+// The SMA, EMA and RSI references are the worked examples published by
+// StockCharts, to two decimal places. The MACD reference is the textbook
+// 12/26/9 MACD, EMAs seeded with an SMA, over a generated series.
+//
+// Our EMAs apply the seed candle's close on top of the SMA seed, so they start a
+// little off the references and converge over the following candles. The
+// checkpoints start once that difference is within the tolerance.
+use crate::error;
+use crate::ma;
+use crate::rsi;
+
+use error::CtError;
+use ma::{MAData, MACD};
+use rsi::Rsi;
+
+// Largest difference from a reference value which still matches.
+static TOLERANCE: f64 = 0.01;
+
+// https://school.stockcharts.com/doku.php?id=technical_indicators:moving_averages
+static MA_CLOSES: [f64; 30] = [
+    22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29, 22.15, 22.39, 22.38,
+    22.61, 23.36, 24.05, 23.75, 23.83, 23.95, 23.63, 23.82, 23.87, 23.65, 23.19, 23.10, 23.33,
+    22.68, 23.10, 22.40, 22.17,
+];
+
+// 10 period SMA and EMA of MA_CLOSES, (candle index, value).
+static SMA_10: [(usize, f64); 5] = [
+    (9, 22.22),
+    (15, 22.61),
+    (20, 23.38),
+    (25, 23.61),
+    (29, 23.13),
+];
+static EMA_10: [(usize, f64); 5] = [
+    (12, 22.27),
+    (15, 22.80),
+    (20, 23.43),
+    (25, 23.39),
+    (29, 22.92),
+];
+
+// https://school.stockcharts.com/doku.php?id=technical_indicators:relative_strength_index_rsi
+static RSI_CLOSES: [f64; 33] = [
+    44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61,
+    46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64, 46.21, 46.25, 45.71, 46.45, 45.78, 45.35,
+    44.03, 44.18, 44.22, 44.57, 43.42, 42.66, 43.13,
+];
+
+// 14 period Wilder RSI of RSI_CLOSES.
+static RSI_14: [(usize, f64); 5] = [
+    (14, 70.46),
+    (18, 66.29),
+    (22, 56.01),
+    (26, 40.02),
+    (32, 37.79),
+];
+
+// MACD line and signal line of macd_closes().
+static MACD_LINE: [(usize, f64); 3] = [(90, 1.468705), (100, 0.476412), (110, 2.793236)];
+static MACD_SIGNAL: [(usize, f64); 3] = [(90, 2.05719), (100, 0.447554), (110, 2.242564)];
+
+// Trending sine wave, long enough for the MACD's EMAs to converge.
+fn macd_closes() -> Vec<f64> {
+    (0..120)
+        .map(|i| 100.0 + 5.0 * (i as f64 / 4.0).sin() + 0.2 * i as f64)
+        .collect()
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Calibrated {
+    Sma,
+    Ema,
+    MacdLine,
+    MacdSignal,
+    Rsi,
+}
+
+// Run the indicator over its reference input, checking its output at each of the
+// reference's checkpoints.
+#[allow(dead_code)]
+pub fn verify_against_reference(indicator: Calibrated) -> Result<(), CtError> {
+    let (outputs, expected): (Vec<Option<f64>>, &[(usize, f64)]) = match indicator {
+        Calibrated::Sma | Calibrated::Ema => {
+            let ema = indicator == Calibrated::Ema;
+            let mut ma = MAData::new(10);
+            let outputs = MA_CLOSES
+                .iter()
+                .map(|cp| {
+                    ma.compute(*cp, ema);
+                    ma.latest()
+                })
+                .collect();
+            (outputs, if ema { &EMA_10 } else { &SMA_10 })
+        }
+        Calibrated::MacdLine | Calibrated::MacdSignal => {
+            let mut macd = MACD::new();
+            let outputs = macd_closes()
+                .iter()
+                .map(|cp| {
+                    macd.compute(*cp);
+                    if indicator == Calibrated::MacdLine {
+                        macd.macd_latest
+                    } else {
+                        macd.signal.latest()
+                    }
+                })
+                .collect();
+            let expected: &[(usize, f64)] = if indicator == Calibrated::MacdLine {
+                &MACD_LINE
+            } else {
+                &MACD_SIGNAL
+            };
+            (outputs, expected)
+        }
+        Calibrated::Rsi => {
+            let mut rsi = Rsi::new(14);
+            let outputs = RSI_CLOSES
+                .iter()
+                .map(|cp| {
+                    rsi.compute(*cp);
+                    rsi.latest()
+                })
+                .collect();
+            (outputs, &RSI_14)
+        }
+    };
+
+    for (index, value) in expected.iter() {
+        let actual = outputs.get(*index).copied().flatten();
+        if !actual.is_some_and(|actual| (actual - value).abs() <= TOLERANCE) {
+            return Err(CtError::CalibrationMismatch {
+                indicator: format!("{:?}", indicator),
+                index: *index,
+                expected: *value,
+                actual,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sma() {
+        assert_eq!(verify_against_reference(Calibrated::Sma), Ok(()));
+    }
+
+    #[test]
+    fn ema() {
+        assert_eq!(verify_against_reference(Calibrated::Ema), Ok(()));
+    }
+
+    #[test]
+    fn macd() {
+        assert_eq!(verify_against_reference(Calibrated::MacdLine), Ok(()));
+        assert_eq!(verify_against_reference(Calibrated::MacdSignal), Ok(()));
+    }
+
+    #[test]
+    fn rsi() {
+        assert_eq!(verify_against_reference(Calibrated::Rsi), Ok(()));
+    }
+}
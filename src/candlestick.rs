@@ -1,5 +1,12 @@
 // structures and routines related to candle sticks.
+use crate::error;
+
+use error::CtError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::num::ParseFloatError;
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CandleStick {
@@ -17,6 +24,22 @@ pub struct CandleStick {
     pub ignore: String,
 }
 
+impl CandleStick {
+    // Convert the exchange's string prices into a numeric candle.
+    pub fn to_candle(&self) -> Result<Candle, ParseFloatError> {
+        Ok(Candle {
+            open_time: self.open_time,
+            close_time: self.close_time,
+            open: self.open_price.parse::<f64>()?,
+            high: self.high_price.parse::<f64>()?,
+            low: self.low_price.parse::<f64>()?,
+            close: self.close_price.parse::<f64>()?,
+            volume: self.vol.parse::<f64>()?,
+            symbol: None,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[allow(non_snake_case)]
 pub struct KLine {
@@ -48,8 +71,421 @@ pub struct CandleStickWs {
     pub k: KLine,  // KLine data.
 }
 
+// A closed candle with numeric prices, used when feeding indicators from
+// sources other than the exchange (files, iterators, tests).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Candle {
+    pub open_time: u64,
+    pub close_time: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    // Symbol the candle is for, when the source says. A tracker with a symbol
+    // rejects candles for another.
+    #[serde(default)]
+    pub symbol: Option<String>,
+}
+
+// Price taken from a candle to compute indicators from.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PriceSource {
+    Close,
+    // (high + low) / 2
+    Hl2,
+    // (high + low + close) / 3, the typical price.
+    Hlc3,
+    // (open + high + low + close) / 4
+    Ohlc4,
+}
+
+impl Candle {
+    pub fn price(&self, source: PriceSource) -> f64 {
+        match source {
+            PriceSource::Close => self.close,
+            PriceSource::Hl2 => (self.high + self.low) / 2.0,
+            PriceSource::Hlc3 => (self.high + self.low + self.close) / 3.0,
+            PriceSource::Ohlc4 => (self.open + self.high + self.low + self.close) / 4.0,
+        }
+    }
+
+    // Closed above its open.
+    pub fn is_bullish(&self) -> bool {
+        self.close > self.open
+    }
+
+    // GREEN unless it closed below its open.
+    #[allow(dead_code)]
+    pub fn color(&self) -> CandleColor {
+        if self.close >= self.open {
+            CandleColor::GREEN
+        } else {
+            CandleColor::RED
+        }
+    }
+
+    // Distance between the open and close.
+    pub fn body(&self) -> f64 {
+        (self.close - self.open).abs()
+    }
+
+    // Distance from the top of the body to the high.
+    pub fn upper_wick(&self) -> f64 {
+        self.high - self.open.max(self.close)
+    }
+
+    // Distance from the bottom of the body to the low.
+    pub fn lower_wick(&self) -> f64 {
+        self.open.min(self.close) - self.low
+    }
+
+    // Distance from the low to the high.
+    pub fn range(&self) -> f64 {
+        self.high - self.low
+    }
+
+    // Build a candle from the "k" object of a websocket kline event.
+    pub fn from_kline_json(k: &serde_json::Value) -> Option<Candle> {
+        let price = |key: &str| k[key].as_str().and_then(|p| p.parse::<f64>().ok());
+
+        Some(Candle {
+            open_time: k["t"].as_u64()?,
+            close_time: k["T"].as_u64()?,
+            open: price("o")?,
+            high: price("h")?,
+            low: price("l")?,
+            close: price("c")?,
+            volume: price("v")?,
+            symbol: k["s"].as_str().map(String::from),
+        })
+    }
+}
+
+// Read candles from newline delimited JSON, one Candle object per line. A line
+// which fails to parse yields an error and the stream carries on, blank lines are
+// skipped.
+#[allow(dead_code)]
+pub fn read_candles_jsonl(reader: impl BufRead) -> impl Iterator<Item = Result<Candle, CtError>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err((i, e.to_string()))),
+            };
+            if line.trim().is_empty() {
+                return None;
+            }
+
+            Some(serde_json::from_str::<Candle>(&line).map_err(|e| (i, e.to_string())))
+        })
+        .map(|result| {
+            result.map_err(|(i, reason)| CtError::ParseCandle {
+                line: i + 1,
+                reason,
+            })
+        })
+}
+
+// Check 'curr' opens exactly one interval after 'prev'.
+pub fn validate_continuity(
+    prev: &Candle,
+    curr: &Candle,
+    expected_interval: Duration,
+) -> Result<(), CtError> {
+    let interval = expected_interval.as_millis() as u64;
+    let elapsed = curr.open_time.saturating_sub(prev.open_time);
+
+    if interval == 0 || elapsed == interval {
+        Ok(())
+    } else if curr.open_time > prev.open_time && elapsed.is_multiple_of(interval) {
+        Err(CtError::CandleGap {
+            missing: elapsed / interval - 1,
+        })
+    } else {
+        Err(CtError::CandleOutOfOrder {
+            prev_open_time: prev.open_time,
+            open_time: curr.open_time,
+        })
+    }
+}
+
+// The most common gap between consecutive open times, so gaps and the odd
+// irregular candle don't throw it off. Ties go to the shorter interval, None
+// without at least two increasing open times.
+#[allow(dead_code)]
+pub fn infer_interval(candles: &[Candle]) -> Option<Duration> {
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for pair in candles.windows(2) {
+        if pair[1].open_time > pair[0].open_time {
+            *counts
+                .entry(pair[1].open_time - pair[0].open_time)
+                .or_insert(0) += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+        .map(|(delta, _)| Duration::from_millis(delta))
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum CandleColor {
     GREEN,
     RED,
 }
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CandlePattern {
+    BullishEngulfing,
+    BearishEngulfing,
+    Doji,
+    Hammer,
+    ShootingStar,
+}
+
+// Proportions detect_pattern_with() uses to recognise the single candle patterns.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PatternRatios {
+    // A doji's body is at most this fraction of its range.
+    pub doji_body: f64,
+    // A hammer or shooting star's long wick is at least this multiple of its body.
+    pub long_wick: f64,
+    // And its other wick at most this fraction of its range.
+    pub short_wick: f64,
+}
+
+impl Default for PatternRatios {
+    fn default() -> Self {
+        PatternRatios {
+            doji_body: 0.1,
+            long_wick: 2.0,
+            short_wick: 0.1,
+        }
+    }
+}
+
+// Pattern formed by 'curr', with 'prev' the candle before it, using the default
+// ratios.
+#[allow(dead_code)]
+pub fn detect_pattern(prev: &Candle, curr: &Candle) -> Option<CandlePattern> {
+    detect_pattern_with(prev, curr, &PatternRatios::default())
+}
+
+// As detect_pattern(), engulfing patterns take precedence over the single candle
+// ones.
+pub fn detect_pattern_with(
+    prev: &Candle,
+    curr: &Candle,
+    ratios: &PatternRatios,
+) -> Option<CandlePattern> {
+    let body = curr.body();
+
+    if body > prev.body() {
+        if prev.close < prev.open
+            && curr.is_bullish()
+            && curr.open <= prev.close
+            && curr.close >= prev.open
+        {
+            return Some(CandlePattern::BullishEngulfing);
+        }
+        if prev.close > prev.open
+            && curr.close < curr.open
+            && curr.open >= prev.close
+            && curr.close <= prev.open
+        {
+            return Some(CandlePattern::BearishEngulfing);
+        }
+    }
+
+    let range = curr.range();
+    if range <= 0.0 {
+        return None;
+    }
+
+    let upper_wick = curr.upper_wick();
+    let lower_wick = curr.lower_wick();
+
+    if body <= ratios.doji_body * range {
+        Some(CandlePattern::Doji)
+    } else if lower_wick >= ratios.long_wick * body && upper_wick <= ratios.short_wick * range {
+        Some(CandlePattern::Hammer)
+    } else if upper_wick >= ratios.long_wick * body && lower_wick <= ratios.short_wick * range {
+        Some(CandlePattern::ShootingStar)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(open_time: u64) -> Candle {
+        Candle {
+            open_time,
+            close_time: open_time + 59_999,
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: 0.0,
+            symbol: None,
+        }
+    }
+
+    #[test]
+    fn price() {
+        let c = Candle {
+            open: 2.0,
+            high: 5.0,
+            low: 1.0,
+            close: 3.0,
+            ..candle(0)
+        };
+
+        assert_eq!(c.price(PriceSource::Close), 3.0);
+        assert_eq!(c.price(PriceSource::Hl2), 3.0);
+        assert_eq!(c.price(PriceSource::Hlc3), (5.0 + 1.0 + 3.0) / 3.0);
+        assert_eq!(c.price(PriceSource::Ohlc4), 2.75);
+    }
+
+    #[test]
+    fn patterns() {
+        let ohlc = |open, high, low, close| Candle {
+            open,
+            high,
+            low,
+            close,
+            ..candle(0)
+        };
+        let red = ohlc(10.0, 10.5, 8.5, 9.0);
+        let green = ohlc(9.0, 10.5, 8.5, 10.0);
+
+        assert_eq!(
+            detect_pattern(&red, &ohlc(8.8, 10.6, 8.7, 10.4)),
+            Some(CandlePattern::BullishEngulfing)
+        );
+        assert_eq!(
+            detect_pattern(&green, &ohlc(10.2, 10.3, 8.5, 8.8)),
+            Some(CandlePattern::BearishEngulfing)
+        );
+        assert_eq!(
+            detect_pattern(&green, &ohlc(10.0, 11.0, 9.0, 10.05)),
+            Some(CandlePattern::Doji)
+        );
+        assert_eq!(
+            detect_pattern(&red, &ohlc(9.6, 10.0, 7.0, 9.9)),
+            Some(CandlePattern::Hammer)
+        );
+        assert_eq!(
+            detect_pattern(&green, &ohlc(10.3, 13.0, 10.0, 10.0)),
+            Some(CandlePattern::ShootingStar)
+        );
+
+        // A plain candle with a full body and even wicks.
+        assert_eq!(detect_pattern(&green, &ohlc(10.0, 10.5, 9.5, 10.25)), None);
+
+        // Looser doji ratio.
+        let ratios = PatternRatios {
+            doji_body: 0.3,
+            ..PatternRatios::default()
+        };
+        assert_eq!(
+            detect_pattern_with(&green, &ohlc(10.0, 10.5, 9.5, 10.25), &ratios),
+            Some(CandlePattern::Doji)
+        );
+    }
+
+    #[test]
+    fn jsonl() {
+        let data = r#"{"open_time":0,"close_time":59999,"open":1.0,"high":2.0,"low":0.5,"close":1.5,"volume":10.0}
+
+            {"open_time":60000,"close":"oops"}
+        "#;
+        let candles: Vec<Result<Candle, CtError>> =
+            read_candles_jsonl(std::io::Cursor::new(data)).collect();
+
+        assert_eq!(candles.len(), 2);
+        assert_eq!(
+            candles[0],
+            Ok(Candle {
+                open_time: 0,
+                close_time: 59_999,
+                open: 1.0,
+                high: 2.0,
+                low: 0.5,
+                close: 1.5,
+                volume: 10.0,
+                symbol: None,
+            })
+        );
+        match &candles[1] {
+            Err(CtError::ParseCandle { line, .. }) => assert_eq!(*line, 3),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn continuity() {
+        let interval = Duration::from_secs(60);
+        assert_eq!(
+            validate_continuity(&candle(0), &candle(60_000), interval),
+            Ok(())
+        );
+        assert_eq!(
+            validate_continuity(&candle(0), &candle(180_000), interval),
+            Err(CtError::CandleGap { missing: 2 })
+        );
+        assert_eq!(
+            validate_continuity(&candle(60_000), &candle(60_000), interval),
+            Err(CtError::CandleOutOfOrder {
+                prev_open_time: 60_000,
+                open_time: 60_000
+            })
+        );
+    }
+
+    #[test]
+    fn anatomy() {
+        let bullish = Candle {
+            open: 10.0,
+            high: 13.0,
+            low: 9.5,
+            close: 12.0,
+            ..candle(0)
+        };
+        assert!(bullish.is_bullish());
+        assert_eq!(bullish.color(), CandleColor::GREEN);
+        assert_eq!(bullish.body(), 2.0);
+        assert_eq!(bullish.upper_wick(), 1.0);
+        assert_eq!(bullish.lower_wick(), 0.5);
+        assert_eq!(bullish.range(), 3.5);
+
+        let doji = Candle {
+            open: 10.0,
+            high: 11.0,
+            low: 9.0,
+            close: 10.0,
+            ..candle(0)
+        };
+        assert!(!doji.is_bullish());
+        assert_eq!(doji.body(), 0.0);
+        assert_eq!(doji.upper_wick(), doji.lower_wick());
+
+        let bearish = Candle { close: 9.5, ..doji };
+        assert_eq!(bearish.color(), CandleColor::RED);
+        assert_eq!(bearish.lower_wick(), 0.5);
+    }
+
+    #[test]
+    fn interval() {
+        let open_times = [0, 60_000, 120_000, 300_000, 360_000, 420_000];
+        let candles: Vec<Candle> = open_times.iter().map(|t| candle(*t)).collect();
+        assert_eq!(infer_interval(&candles), Some(Duration::from_secs(60)));
+        assert_eq!(infer_interval(&candles[..1]), None);
+    }
+}
@@ -0,0 +1,123 @@
+// Connors RSI (connors.rs), the average of a short RSI of the close, an RSI of the
+// up/down close streak and the percent rank of the latest close to close change,
+// a 0..100 mean reversion oscillator.
+// https://www.tradingview.com/support/solutions/43000502017-connors-rsi-crsi/
+use crate::percentile;
+use crate::roc;
+use crate::rsi;
+use crate::streak;
+
+use percentile::PercentRank;
+use roc::Roc;
+use rsi::Rsi;
+use streak::CloseStreak;
+
+// Default periods of the close RSI, streak RSI and the percent rank history.
+static CONNORS_RSI_PERIOD: u16 = 3;
+static CONNORS_STREAK_PERIOD: u16 = 2;
+static CONNORS_RANK_PERIOD: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct ConnorsRsi {
+    rsi: Rsi,
+    streak: CloseStreak,
+    streak_rsi: Rsi,
+    roc: Roc,
+    roc_rank: PercentRank,
+
+    latest: Option<f64>,
+}
+
+impl ConnorsRsi {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        ConnorsRsi::with_periods(
+            CONNORS_RSI_PERIOD,
+            CONNORS_STREAK_PERIOD,
+            CONNORS_RANK_PERIOD,
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn with_periods(rsi_period: u16, streak_period: u16, rank_period: usize) -> Self {
+        ConnorsRsi {
+            rsi: Rsi::new(rsi_period),
+            streak: CloseStreak::new(),
+            streak_rsi: Rsi::new(streak_period),
+            roc: Roc::new(1),
+            roc_rank: PercentRank::new(rank_period),
+            latest: None,
+        }
+    }
+
+    // Current Connors RSI value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        self.rsi.compute(close_price);
+        self.streak.compute(close_price);
+        self.streak_rsi.compute(self.streak.streak() as f64);
+        self.roc.compute(close_price);
+        if let Some(roc) = self.roc.latest() {
+            self.roc_rank.compute(roc);
+        }
+
+        if let (Some(rsi), Some(streak_rsi), Some(rank)) = (
+            self.rsi.latest(),
+            self.streak_rsi.latest(),
+            self.roc_rank.latest(),
+        ) {
+            self.latest = Some((rsi + streak_rsi + rank) / 3.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Closes chopping up and down by up to 1%.
+    fn sideways(crsi: &mut ConnorsRsi) -> f64 {
+        let mut cp = 100.0;
+        for i in 0..150 {
+            cp *= 1.0 + (((i * 7) % 5) as f64 - 2.0) / 200.0;
+            crsi.compute(cp);
+        }
+        cp
+    }
+
+    #[test]
+    fn multi_day_decline() {
+        let mut crsi = ConnorsRsi::new();
+        let mut cp = sideways(&mut crsi);
+        let before = crsi.latest().unwrap();
+        assert!(before > 10.0 && before < 90.0);
+
+        for _ in 0..5 {
+            cp *= 0.98;
+            crsi.compute(cp);
+        }
+        assert!(crsi.latest().unwrap() < 10.0, "{:?}", crsi.latest());
+    }
+
+    #[test]
+    fn multi_day_rally() {
+        let mut crsi = ConnorsRsi::new();
+        let mut cp = sideways(&mut crsi);
+        for _ in 0..5 {
+            cp *= 1.02;
+            crsi.compute(cp);
+        }
+
+        let latest = crsi.latest().unwrap();
+        assert!(latest > 90.0 && latest <= 100.0, "{}", latest);
+    }
+}
@@ -0,0 +1,97 @@
+// Rolling correlation (correlation.rs), Pearson correlation of two price series
+// over a window, e.g. to spot a normally correlated pair diverging.
+use crate::window;
+
+use window::RollingWindow;
+
+#[derive(Debug, Clone)]
+pub struct RollingCorrelation {
+    // Running sums of x, y, xy, x^2 and y^2 over the window.
+    x: RollingWindow<f64>,
+    y: RollingWindow<f64>,
+    xy: RollingWindow<f64>,
+    xx: RollingWindow<f64>,
+    yy: RollingWindow<f64>,
+
+    latest: Option<f64>,
+}
+
+impl RollingCorrelation {
+    #[allow(dead_code)]
+    pub fn new(window: usize) -> Self {
+        RollingCorrelation {
+            x: RollingWindow::new(window),
+            y: RollingWindow::new(window),
+            xy: RollingWindow::new(window),
+            xx: RollingWindow::new(window),
+            yy: RollingWindow::new(window),
+            latest: None,
+        }
+    }
+
+    // Current correlation, from -1 to 1. None until the window is full or while
+    // either series is flat.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Add the latest prices of both series.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, price_a: f64, price_b: f64) {
+        if !price_a.is_finite() || !price_b.is_finite() {
+            return;
+        }
+
+        self.x.push(price_a);
+        self.y.push(price_b);
+        self.xy.push(price_a * price_b);
+        self.xx.push(price_a * price_a);
+        self.yy.push(price_b * price_b);
+        if self.x.cap() < 2 || !self.x.is_full() {
+            return;
+        }
+
+        let n = self.x.len() as f64;
+        let (sum_x, sum_y) = (self.x.sum(), self.y.sum());
+        let cov = n * self.xy.sum() - sum_x * sum_y;
+        let var_x = n * self.xx.sum() - sum_x * sum_x;
+        let var_y = n * self.yy.sum() - sum_y * sum_y;
+
+        // Rounding leaves a flat series with a tiny variance rather than zero.
+        let flat = |var: f64, sum_sq: f64| var <= f64::EPSILON * n * sum_sq;
+        self.latest = if !flat(var_x, self.xx.sum()) && !flat(var_y, self.yy.sum()) {
+            Some((cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0))
+        } else {
+            None
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correlated() {
+        let mut same = RollingCorrelation::new(10);
+        let mut opposite = RollingCorrelation::new(10);
+        for i in 0..30 {
+            let a = 100.0 + 5.0 * (i as f64 / 3.0).sin();
+            same.compute(a, 2.0 * a + 7.0);
+            opposite.compute(a, 500.0 - 3.0 * a);
+        }
+
+        assert!((same.latest().unwrap() - 1.0).abs() < 1e-6);
+        assert!((opposite.latest().unwrap() + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn flat() {
+        let mut flat = RollingCorrelation::new(3);
+        for i in 0..5 {
+            flat.compute(0.1 + 0.2, i as f64);
+        }
+        assert!(flat.latest().is_none());
+    }
+}
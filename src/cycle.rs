@@ -0,0 +1,160 @@
+// Dominant cycle period (cycle.rs), Ehlers' Hilbert transform homodyne
+// discriminator estimate of the current cycle length in candles, for indicators
+// which tune their period to the market.
+// John F. Ehlers, Rocket Science for Traders, chapter 7.
+use crate::window;
+
+use std::f64::consts::PI;
+use window::RollingWindow;
+
+// Candles processed before the estimate is considered settled.
+static CYCLE_WARMUP: u32 = 32;
+
+// Limits on the estimated period.
+static MIN_PERIOD: f64 = 6.0;
+static MAX_PERIOD: f64 = 50.0;
+
+#[derive(Debug)]
+pub struct DominantCycle {
+    // Histories, newest first.
+    closes: RollingWindow<f64>,
+    smoothed: RollingWindow<f64>,
+    detrender: RollingWindow<f64>,
+    i1: RollingWindow<f64>,
+    q1: RollingWindow<f64>,
+
+    // Previous values of the smoothed phasor components.
+    i2: f64,
+    q2: f64,
+    re: f64,
+    im: f64,
+
+    period: f64,
+    smooth_period: f64,
+    num_candles: u32,
+}
+
+// The value 'n' candles back in the history, 0.0 before there is one.
+fn back(window: &RollingWindow<f64>, n: usize) -> f64 {
+    window.iter().nth(n).copied().unwrap_or(0.0)
+}
+
+// Ehlers' Hilbert transform FIR over the history.
+fn hilbert(window: &RollingWindow<f64>) -> f64 {
+    0.0962 * back(window, 0) + 0.5769 * back(window, 2)
+        - 0.5769 * back(window, 4)
+        - 0.0962 * back(window, 6)
+}
+
+impl DominantCycle {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        DominantCycle {
+            closes: RollingWindow::new(4),
+            smoothed: RollingWindow::new(7),
+            detrender: RollingWindow::new(7),
+            i1: RollingWindow::new(7),
+            q1: RollingWindow::new(7),
+            i2: 0.0,
+            q2: 0.0,
+            re: 0.0,
+            im: 0.0,
+            period: 0.0,
+            smooth_period: 0.0,
+            num_candles: 0,
+        }
+    }
+
+    // Current dominant cycle length, in candles.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        if self.num_candles >= CYCLE_WARMUP {
+            Some(self.smooth_period)
+        } else {
+            None
+        }
+    }
+
+    // Update the estimate with the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        self.num_candles += 1;
+        self.closes.push(close_price);
+
+        // Smooth out the shortest cycles with a 4 candle WMA.
+        let smoothed = (4.0 * back(&self.closes, 0)
+            + 3.0 * back(&self.closes, 1)
+            + 2.0 * back(&self.closes, 2)
+            + back(&self.closes, 3))
+            / 10.0;
+        self.smoothed.push(smoothed);
+
+        let adjustment = 0.075 * self.period + 0.54;
+        self.detrender.push(hilbert(&self.smoothed) * adjustment);
+
+        // In phase and quadrature components.
+        let q1 = hilbert(&self.detrender) * adjustment;
+        let i1 = back(&self.detrender, 3);
+        self.q1.push(q1);
+        self.i1.push(i1);
+
+        // Advance the phase of each by 90 degrees.
+        let ji = hilbert(&self.i1) * adjustment;
+        let jq = hilbert(&self.q1) * adjustment;
+
+        // Phasor addition, smoothed.
+        let i2 = 0.2 * (i1 - jq) + 0.8 * self.i2;
+        let q2 = 0.2 * (q1 + ji) + 0.8 * self.q2;
+
+        // Homodyne discriminator, the phase change since the previous candle.
+        let re = 0.2 * (i2 * self.i2 + q2 * self.q2) + 0.8 * self.re;
+        let im = 0.2 * (i2 * self.q2 - q2 * self.i2) + 0.8 * self.im;
+        self.i2 = i2;
+        self.q2 = q2;
+        self.re = re;
+        self.im = im;
+
+        let mut period = self.period;
+        if im != 0.0 && re != 0.0 {
+            period = 2.0 * PI / (im / re).atan();
+        }
+        period = period
+            .min(1.5 * self.period)
+            .max(0.67 * self.period)
+            .max(MIN_PERIOD)
+            .min(MAX_PERIOD);
+
+        self.period = 0.2 * period + 0.8 * self.period;
+        self.smooth_period = 0.33 * self.period + 0.67 * self.smooth_period;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sine_wave() {
+        for cycle in [15.0, 20.0, 30.0].iter() {
+            let mut dc = DominantCycle::new();
+            for i in 0..400 {
+                dc.compute(100.0 + 5.0 * (2.0 * PI * i as f64 / cycle).sin());
+                if i < 30 {
+                    assert!(dc.latest().is_none());
+                }
+            }
+
+            let estimate = dc.latest().unwrap();
+            assert!(
+                (estimate - cycle).abs() < 0.1 * cycle,
+                "cycle {} estimated as {}",
+                cycle,
+                estimate
+            );
+        }
+    }
+}
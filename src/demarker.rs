@@ -0,0 +1,134 @@
+// DeMarker (demarker.rs), compares each high and low with the previous candle's
+// to gauge demand. 0..1, above 0.7 is overbought and below 0.3 oversold.
+// https://www.investopedia.com/terms/d/demarkerindicator.asp
+use crate::ma;
+use crate::position;
+
+use ma::MAData;
+use position::PositionType;
+
+// Signal levels, buy on crossing up through the lower and sell on crossing down
+// through the upper.
+#[allow(dead_code)]
+pub static DEMARKER_UPPER: f64 = 0.7;
+#[allow(dead_code)]
+pub static DEMARKER_LOWER: f64 = 0.3;
+
+#[derive(Debug)]
+pub struct DeMarker {
+    prev_high: Option<f64>,
+    prev_low: Option<f64>,
+
+    // SMAs of the rises in the high and falls in the low.
+    de_max: MAData,
+    de_min: MAData,
+
+    latest: Option<f64>,
+    previous: Option<f64>,
+}
+
+impl DeMarker {
+    #[allow(dead_code)]
+    pub fn new(period: u16) -> Self {
+        DeMarker {
+            prev_high: None,
+            prev_low: None,
+            de_max: MAData::new(period),
+            de_min: MAData::new(period),
+            latest: None,
+            previous: None,
+        }
+    }
+
+    // Current DeMarker value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // DeMarker crossing the 0.3/0.7 levels, returns:
+    // PositionType::Long if it crosses up through 0.3.
+    // PositionType::Short if it crosses down through 0.7.
+    #[allow(dead_code)]
+    pub fn signal(&self) -> PositionType {
+        if let (Some(latest), Some(previous)) = (self.latest, self.previous) {
+            if latest > DEMARKER_LOWER && previous <= DEMARKER_LOWER {
+                return PositionType::Long;
+            } else if latest < DEMARKER_UPPER && previous >= DEMARKER_UPPER {
+                return PositionType::Short;
+            }
+        }
+
+        PositionType::None
+    }
+
+    // Compute the latest value from the candle's high and low.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, high: f64, low: f64) {
+        if !high.is_finite() || !low.is_finite() {
+            return;
+        }
+
+        if let (Some(prev_high), Some(prev_low)) = (self.prev_high, self.prev_low) {
+            self.de_max.compute((high - prev_high).max(0.0), false);
+            self.de_min.compute((prev_low - low).max(0.0), false);
+
+            if let (Some(de_max), Some(de_min)) = (self.de_max.latest(), self.de_min.latest()) {
+                self.previous = self.latest;
+                self.latest = if de_max + de_min > 0.0 {
+                    Some(de_max / (de_max + de_min))
+                } else {
+                    Some(0.5)
+                };
+            }
+        }
+
+        self.prev_high = Some(high);
+        self.prev_low = Some(low);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rising_highs() {
+        let mut dem = DeMarker::new(5);
+        for i in 0..5 {
+            dem.compute(10.0 + i as f64, 9.0);
+            assert!(dem.latest().is_none());
+        }
+
+        // Each high above the last, one dip among the lows.
+        for i in 5..12 {
+            let low = if i == 8 { 8.5 } else { 9.0 };
+            dem.compute(10.0 + i as f64, low);
+        }
+        let dem_latest = dem.latest().unwrap();
+        assert!(dem_latest > DEMARKER_UPPER && dem_latest < 1.0);
+
+        for i in 12..20 {
+            dem.compute(10.0 + i as f64, 9.0 + i as f64);
+        }
+        assert_eq!(dem.latest(), Some(1.0));
+
+        // Falling highs drag it back down through the upper level.
+        let mut signals = vec![];
+        for i in 0..5 {
+            dem.compute(29.0 - i as f64, 28.0);
+            signals.push(dem.signal());
+        }
+        assert!(signals.contains(&PositionType::Short));
+        assert!(!signals.contains(&PositionType::Long));
+    }
+
+    #[test]
+    fn flat() {
+        let mut dem = DeMarker::new(3);
+        for _ in 0..5 {
+            dem.compute(10.0, 9.0);
+        }
+        assert_eq!(dem.latest(), Some(0.5));
+    }
+}
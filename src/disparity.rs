@@ -0,0 +1,71 @@
+// Disparity index (disparity.rs), percentage distance of the close from its moving average.
+use crate::ma;
+
+use ma::MAData;
+
+#[derive(Debug)]
+pub struct DisparityIndex {
+    pub ma: MAData,
+    // Exponential or simple MA.
+    pub ema: bool,
+    latest: Option<f64>,
+}
+
+impl DisparityIndex {
+    #[allow(dead_code)]
+    pub fn new(num_candles: u16, ema: bool) -> Self {
+        DisparityIndex {
+            ma: MAData::new(num_candles),
+            ema,
+            latest: None,
+        }
+    }
+
+    // Current disparity, as a percentage of the MA.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Compute the latest disparity based on the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        self.ma.compute(close_price, self.ema);
+        if let Some(ma) = self.ma.latest() {
+            self.latest = if ma != 0.0 {
+                Some(100.0 * (close_price - ma) / ma)
+            } else {
+                None
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn above_ma() {
+        let mut di = DisparityIndex::new(3, false);
+        di.compute(10.0);
+        di.compute(10.0);
+        assert!(di.latest().is_none());
+
+        // SMA of [10, 10, x] is (20 + x) / 3, x is 10% above that when x = 22 / 1.9.
+        di.compute(22.0 / 1.9);
+        assert!((di.latest().unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zero_ma() {
+        let mut di = DisparityIndex::new(2, false);
+        di.compute(0.0);
+        di.compute(0.0);
+        assert!(di.latest().is_none());
+    }
+}
@@ -0,0 +1,92 @@
+// Detrended price oscillator (dpo.rs), the close shifted back period / 2 + 1
+// candles less the SMA, which removes the trend to leave the shorter cycles.
+// https://www.investopedia.com/terms/d/detrended-price-oscillator-dpo.asp
+use crate::ma;
+use crate::window;
+
+use ma::MAData;
+use window::RollingWindow;
+
+#[derive(Debug)]
+pub struct Dpo {
+    latest: Option<f64>,
+
+    // SMA of the close over the period.
+    ma: MAData,
+    // Close prices, newest at the front. The oldest is the shifted close.
+    closes: RollingWindow<f64>,
+    pub period: u16,
+}
+
+impl Dpo {
+    #[allow(dead_code)]
+    pub fn new(period: u16) -> Self {
+        Dpo {
+            latest: None,
+            ma: MAData::new(period),
+            closes: RollingWindow::new(period as usize / 2 + 2),
+            period,
+        }
+    }
+
+    // Current detrended price oscillator value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Compute the latest DPO value based on the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if self.period == 0 || !close_price.is_finite() {
+            return;
+        }
+
+        self.ma.compute(close_price, false);
+        self.closes.push(close_price);
+
+        if let (Some(ma), Some(shifted)) = (self.ma.latest(), self.closes.oldest()) {
+            if self.closes.is_full() {
+                self.latest = Some(shifted - ma);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_trend() {
+        let mut dpo = Dpo::new(20);
+        for i in 0..200 {
+            dpo.compute(100.0 + i as f64);
+            if i < 19 {
+                assert!(dpo.latest().is_none());
+            } else {
+                // The trend is removed, leaving the same small offset however
+                // far the price has moved.
+                assert!((dpo.latest().unwrap() + 1.5).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn cycle_on_trend() {
+        let mut dpo = Dpo::new(20);
+        let mut values = Vec::new();
+        for i in 0..200 {
+            let cycle = 5.0 * (2.0 * std::f64::consts::PI * i as f64 / 20.0).sin();
+            dpo.compute(100.0 + 0.1 * i as f64 + cycle);
+            if let Some(v) = dpo.latest() {
+                values.push(v);
+            }
+        }
+
+        assert!(values.iter().any(|v| *v > 4.0));
+        assert!(values.iter().any(|v| *v < -4.0));
+        let mean = values[..180].iter().sum::<f64>() / 180.0;
+        assert!(mean.abs() < 0.5);
+    }
+}
@@ -0,0 +1,80 @@
+// Errors (error.rs) from validating and processing market data.
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CtError {
+    // Candles missing between two consecutive candles.
+    CandleGap {
+        missing: u64,
+    },
+    // Candle which doesn't start a whole interval after the previous one.
+    CandleOutOfOrder {
+        prev_open_time: u64,
+        open_time: u64,
+    },
+    // Candle for a symbol other than the one being tracked.
+    SymbolMismatch {
+        expected: String,
+        actual: String,
+    },
+    // Candle with a NaN or inf price.
+    NonFinitePrice,
+    // Line 'line' (from 1) of a candle file which couldn't be read or parsed.
+    ParseCandle {
+        line: usize,
+        reason: String,
+    },
+    // Text which doesn't name a known value, e.g. a PositionType.
+    Parse {
+        input: String,
+    },
+    // MA weights which don't match the window length, or are negative or all zero.
+    InvalidWeights,
+    // Indicator output at candle 'index' which doesn't match its reference series.
+    CalibrationMismatch {
+        indicator: String,
+        index: usize,
+        expected: f64,
+        actual: Option<f64>,
+    },
+}
+
+impl fmt::Display for CtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CtError::CandleGap { missing } => write!(f, "{} candle(s) missing", missing),
+            CtError::CandleOutOfOrder {
+                prev_open_time,
+                open_time,
+            } => write!(
+                f,
+                "candle opening at {} doesn't follow the candle opening at {}",
+                open_time, prev_open_time
+            ),
+            CtError::SymbolMismatch { expected, actual } => {
+                write!(f, "candle is for {}, expected {}", actual, expected)
+            }
+            CtError::NonFinitePrice => write!(f, "candle has non-finite prices"),
+            CtError::ParseCandle { line, reason } => {
+                write!(f, "failed to parse candle on line {}: {}", line, reason)
+            }
+            CtError::Parse { input } => write!(f, "failed to parse {:?}", input),
+            CtError::InvalidWeights => write!(
+                f,
+                "MA weights must be one non-negative weight per candle, not all zero"
+            ),
+            CtError::CalibrationMismatch {
+                indicator,
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} at candle {} is {:?}, the reference is {}",
+                indicator, index, actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CtError {}
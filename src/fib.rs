@@ -0,0 +1,72 @@
+// Fibonacci retracement levels (fib.rs), prices a swing commonly pulls back to
+// before continuing, used for pullback entries and targets.
+// https://www.investopedia.com/terms/f/fibonacciretracement.asp
+use crate::position;
+
+use position::PositionType;
+
+// Retracement ratios, measured back from the end of the swing.
+static RATIOS: [f64; 5] = [0.236, 0.382, 0.5, 0.618, 0.786];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FibLevels {
+    pub level_236: f64,
+    pub level_382: f64,
+    pub level_500: f64,
+    pub level_618: f64,
+    pub level_786: f64,
+}
+
+impl FibLevels {
+    // Levels ordered from the shallowest retracement to the deepest.
+    #[allow(dead_code)]
+    pub fn levels(&self) -> [f64; 5] {
+        [
+            self.level_236,
+            self.level_382,
+            self.level_500,
+            self.level_618,
+            self.level_786,
+        ]
+    }
+}
+
+// Retracement prices for a swing between swing_low and swing_high. A Long
+// direction is an up-swing, which retraces down from the high, a Short direction
+// is a down-swing retracing up from the low. None is treated as an up-swing.
+#[allow(dead_code)]
+pub fn fib_levels(swing_high: f64, swing_low: f64, direction: PositionType) -> FibLevels {
+    let range = swing_high - swing_low;
+    let level = |ratio: f64| match direction {
+        PositionType::Short => swing_low + range * ratio,
+        _ => swing_high - range * ratio,
+    };
+
+    FibLevels {
+        level_236: level(RATIOS[0]),
+        level_382: level(RATIOS[1]),
+        level_500: level(RATIOS[2]),
+        level_618: level(RATIOS[3]),
+        level_786: level(RATIOS[4]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn up_swing() {
+        let fib = fib_levels(200.0, 100.0, PositionType::Long);
+        assert!((fib.level_618 - 138.2).abs() < 1e-9);
+        assert!((fib.level_500 - 150.0).abs() < 1e-9);
+        assert!(fib.levels().windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn down_swing() {
+        let fib = fib_levels(200.0, 100.0, PositionType::Short);
+        assert!((fib.level_618 - 161.8).abs() < 1e-9);
+        assert!(fib.levels().windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -0,0 +1,89 @@
+// Signal hysteresis (hysteresis.rs), an oscillator must cross back through a
+// reset level before its trigger level fires again, so values flickering around
+// the trigger only signal once.
+
+// Fires when the value crosses 'enter', once armed by the value crossing 'exit'.
+// With enter above exit the value has to fall below exit then rise above enter,
+// e.g. an RSI long with { enter: 30.0, exit: 25.0 }. With enter below exit it's
+// the reverse, e.g. an RSI short with { enter: 70.0, exit: 75.0 }.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hysteresis {
+    pub enter: f64,
+    pub exit: f64,
+    armed: bool,
+}
+
+impl Hysteresis {
+    #[allow(dead_code)]
+    pub fn new(enter: f64, exit: f64) -> Self {
+        Hysteresis {
+            enter,
+            exit,
+            armed: false,
+        }
+    }
+
+    // Whether the value has crossed the exit level since the last fire.
+    #[allow(dead_code)]
+    pub fn armed(&self) -> bool {
+        self.armed
+    }
+
+    // Check the latest value, returns true when it fires.
+    pub fn update(&mut self, value: f64) -> bool {
+        if !value.is_finite() {
+            return false;
+        }
+
+        let rising = self.enter >= self.exit;
+        let (past_exit, past_enter) = if rising {
+            (value < self.exit, value > self.enter)
+        } else {
+            (value > self.exit, value < self.enter)
+        };
+
+        if past_exit {
+            self.armed = true;
+        } else if self.armed && past_enter {
+            self.armed = false;
+            return true;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fires_once() {
+        let mut long = Hysteresis::new(30.0, 25.0);
+        // Not armed yet, crossing 30 does nothing.
+        assert!(!long.update(32.0));
+
+        assert!(!long.update(24.0));
+        assert!(long.armed());
+        let fires = [26.0, 32.0, 26.0, 32.0, 26.0, 32.0, 26.0]
+            .iter()
+            .filter(|v| long.update(**v))
+            .count();
+        assert_eq!(fires, 1);
+
+        // Re-armed by dropping below 25 again.
+        long.update(20.0);
+        assert!(long.update(31.0));
+    }
+
+    #[test]
+    fn falling() {
+        let mut short = Hysteresis::new(70.0, 75.0);
+        assert!(!short.update(80.0));
+        let fires = [72.0, 68.0, 72.0, 68.0]
+            .iter()
+            .filter(|v| short.update(**v))
+            .count();
+        assert_eq!(fires, 1);
+    }
+}
@@ -0,0 +1,101 @@
+// Elder Impulse System (impulse.rs), colors each candle by whether the trend EMA
+// and the MACD histogram agree on direction.
+// https://school.stockcharts.com/doku.php?id=chart_analysis:elder_impulse_system
+use crate::position;
+use crate::process_md;
+
+use position::PositionType;
+use process_md::MarketDataTracker;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ImpulseColor {
+    // EMA and histogram both rising.
+    Green,
+    // EMA and histogram both falling.
+    Red,
+    // Mixed, or not enough data yet.
+    Blue,
+}
+
+impl ImpulseColor {
+    // Green permits longs, Red permits shorts, Blue neither new entry.
+    #[allow(dead_code)]
+    pub fn permits(&self, direction: PositionType) -> bool {
+        matches!(
+            (self, direction),
+            (ImpulseColor::Green, PositionType::Long) | (ImpulseColor::Red, PositionType::Short)
+        )
+    }
+}
+
+// Impulse color from the tracker's MACD trend MA and MACD histogram.
+#[allow(dead_code)]
+pub fn impulse_color(mt: &MarketDataTracker) -> ImpulseColor {
+    let ema_slope = mt.macd_trend_ma.slope();
+    let histogram_slope = mt
+        .macd
+        .distance_from_signal()
+        .zip(mt.macd.previous_distance_from_signal())
+        .map(|(latest, prev)| latest - prev);
+
+    match (ema_slope, histogram_slope) {
+        (Some(ema), Some(histogram)) if ema > 0.0 && histogram > 0.0 => ImpulseColor::Green,
+        (Some(ema), Some(histogram)) if ema < 0.0 && histogram < 0.0 => ImpulseColor::Red,
+        _ => ImpulseColor::Blue,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ma;
+    use process_md::TradeSignal;
+
+    // Tracker with the MACD fed 'macd_closes' and the trend EMA 'ema_closes'.
+    fn tracker(macd_closes: &[f64], ema_closes: &[f64]) -> MarketDataTracker {
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        mt.macd_trend_ma = ma::MAData::new(5);
+        for cp in macd_closes {
+            mt.macd.compute(*cp);
+        }
+        for cp in ema_closes {
+            mt.macd_trend_ma.compute(*cp, true);
+        }
+        mt
+    }
+
+    // Flat then accelerating, so the histogram is rising at the end.
+    fn rising() -> Vec<f64> {
+        (0..50)
+            .map(|i| {
+                if i < 40 {
+                    100.0
+                } else {
+                    100.0 + ((i - 39) * (i - 39)) as f64
+                }
+            })
+            .collect()
+    }
+
+    fn falling() -> Vec<f64> {
+        rising().iter().map(|cp| 300.0 - cp).collect()
+    }
+
+    #[test]
+    fn colors() {
+        let green = impulse_color(&tracker(&rising(), &rising()));
+        assert_eq!(green, ImpulseColor::Green);
+        assert!(green.permits(PositionType::Long));
+        assert!(!green.permits(PositionType::Short));
+
+        let red = impulse_color(&tracker(&falling(), &falling()));
+        assert_eq!(red, ImpulseColor::Red);
+        assert!(red.permits(PositionType::Short));
+
+        let blue = impulse_color(&tracker(&rising(), &falling()));
+        assert_eq!(blue, ImpulseColor::Blue);
+        assert!(!blue.permits(PositionType::Long) && !blue.permits(PositionType::Short));
+
+        assert_eq!(impulse_color(&tracker(&[], &[])), ImpulseColor::Blue);
+    }
+}
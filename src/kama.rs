@@ -0,0 +1,120 @@
+// Kaufman adaptive moving average (kama.rs), an EMA whose smoothing adapts between
+// fast and slow based on how efficiently the price has moved over the window.
+// https://www.investopedia.com/terms/k/kaufman-adaptive-moving-average-kama.asp
+use crate::window;
+
+use window::RollingWindow;
+
+// Default fast and slow EMA lengths bounding the smoothing constant.
+static DEFAULT_FAST: u16 = 2;
+static DEFAULT_SLOW: u16 = 30;
+
+#[derive(Debug)]
+pub struct Kama {
+    latest: Option<f64>,
+
+    // Close prices, newest at the front. Holds period + 1 values.
+    closes: RollingWindow<f64>,
+    // Absolute change between consecutive closes over the period.
+    changes: RollingWindow<f64>,
+
+    // Number of candles the efficiency ratio is measured over.
+    pub period: u16,
+    fast_sc: f64,
+    slow_sc: f64,
+}
+
+impl Kama {
+    #[allow(dead_code)]
+    pub fn new(period: u16) -> Self {
+        Kama::with_limits(period, DEFAULT_FAST, DEFAULT_SLOW)
+    }
+
+    // KAMA with custom fast and slow EMA lengths.
+    #[allow(dead_code)]
+    pub fn with_limits(period: u16, fast: u16, slow: u16) -> Self {
+        Kama {
+            latest: None,
+            closes: RollingWindow::new(period as usize + 1),
+            changes: RollingWindow::new(period as usize),
+            period,
+            fast_sc: 2.0 / (fast as f64 + 1.0),
+            slow_sc: 2.0 / (slow as f64 + 1.0),
+        }
+    }
+
+    // Current KAMA value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Efficiency ratio, net change over the summed absolute changes. 1.0 for a
+    // move in a straight line, near 0.0 for noise.
+    fn efficiency_ratio(&self, close_price: f64) -> f64 {
+        let volatility = self.changes.sum();
+        if volatility > 0.0 {
+            (close_price - self.closes.oldest().unwrap()).abs() / volatility
+        } else {
+            0.0
+        }
+    }
+
+    // Compute the latest KAMA value based on the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if self.period == 0 || !close_price.is_finite() {
+            return;
+        }
+
+        if let Some(prev_close) = self.closes.newest() {
+            self.changes.push((close_price - prev_close).abs());
+        }
+        self.closes.push(close_price);
+
+        if !self.closes.is_full() {
+            return;
+        }
+
+        let er = self.efficiency_ratio(close_price);
+        let sc = (er * (self.fast_sc - self.slow_sc) + self.slow_sc).powi(2);
+        let prev = match self.latest {
+            Some(prev) => prev,
+            // Start from the previous close.
+            None => *self.closes.iter().nth(1).unwrap(),
+        };
+
+        self.latest = Some(prev + sc * (close_price - prev));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trend() {
+        let mut kama = Kama::new(10);
+        for i in 0..100 {
+            let cp = 100.0 + i as f64;
+            kama.compute(cp);
+            if i >= 20 {
+                assert!((cp - kama.latest().unwrap()).abs() < 2.0);
+            }
+        }
+    }
+
+    #[test]
+    fn noise() {
+        let mut kama = Kama::new(10);
+        for i in 0..20 {
+            kama.compute(if i % 2 == 0 { 100.0 } else { 102.0 });
+        }
+
+        let start = kama.latest().unwrap();
+        for i in 0..50 {
+            kama.compute(if i % 2 == 0 { 100.0 } else { 102.0 });
+            assert!((kama.latest().unwrap() - start).abs() < 0.25);
+        }
+    }
+}
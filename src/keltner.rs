@@ -0,0 +1,90 @@
+// Keltner channels (keltner.rs), a moving average of the close with bands a
+// multiple of the average true range either side of it.
+// https://www.investopedia.com/terms/k/keltnerchannel.asp
+use crate::ma;
+use crate::prevclose;
+
+use ma::MAData;
+use prevclose::PrevClose;
+
+#[derive(Debug)]
+pub struct KeltnerChannels {
+    pub ma: MAData,
+    // Average true range, over the same number of candles as the middle line.
+    pub atr: MAData,
+    // Number of ATRs between the middle and outer bands.
+    pub multiplier: f64,
+    // Exponential or simple MAs.
+    pub ema: bool,
+
+    prev_close: PrevClose,
+}
+
+impl KeltnerChannels {
+    #[allow(dead_code)]
+    pub fn new(num_candles: u16, multiplier: f64, ema: bool) -> Self {
+        KeltnerChannels {
+            ma: MAData::new(num_candles),
+            atr: MAData::new(num_candles),
+            multiplier,
+            ema,
+            prev_close: PrevClose::new(),
+        }
+    }
+
+    // Middle line, the MA of the close.
+    #[allow(dead_code)]
+    pub fn middle(&self) -> Option<f64> {
+        self.ma.latest()
+    }
+
+    // Upper band.
+    #[allow(dead_code)]
+    pub fn upper(&self) -> Option<f64> {
+        Some(self.ma.latest()? + (self.multiplier * self.atr.latest()?))
+    }
+
+    // Lower band.
+    #[allow(dead_code)]
+    pub fn lower(&self) -> Option<f64> {
+        Some(self.ma.latest()? - (self.multiplier * self.atr.latest()?))
+    }
+
+    // Compute the latest channels from the candle's high, low and close.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, high: f64, low: f64, close: f64) {
+        if !(high.is_finite() && low.is_finite() && close.is_finite()) {
+            return;
+        }
+
+        // The first candle has no previous close, its range is its true range.
+        let true_range = match self.prev_close.prev() {
+            Some(prev_close) => high.max(prev_close) - low.min(prev_close),
+            None => high - low,
+        };
+        self.prev_close.update(close);
+
+        self.ma.compute(close, self.ema);
+        self.atr.compute(true_range, self.ema);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels() {
+        let mut kc = KeltnerChannels::new(3, 2.0, false);
+        kc.compute(11.0, 9.0, 10.0);
+        kc.compute(12.0, 10.0, 11.0);
+        assert!(kc.upper().is_none());
+
+        // Gaps up, the true range runs from the previous close of 11.
+        kc.compute(15.0, 13.0, 14.0);
+        assert!((kc.middle().unwrap() - 35.0 / 3.0).abs() < 1e-9);
+        assert!((kc.atr.latest().unwrap() - 8.0 / 3.0).abs() < 1e-9);
+        assert!((kc.upper().unwrap() - (35.0 + 16.0) / 3.0).abs() < 1e-9);
+        assert!((kc.lower().unwrap() - (35.0 - 16.0) / 3.0).abs() < 1e-9);
+    }
+}
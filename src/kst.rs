@@ -0,0 +1,112 @@
+// Know Sure Thing (kst.rs), a weighted sum of four smoothed rates of change.
+// https://www.investopedia.com/terms/k/know-sure-thing-kst.asp
+use crate::ma;
+use crate::roc;
+
+use ma::MAData;
+use roc::Roc;
+
+// (ROC period, SMA smoothing period, weight) for each KST component.
+static KST_COMPONENTS: [(u16, u16, f64); 4] =
+    [(10, 10, 1.0), (15, 10, 2.0), (20, 10, 3.0), (30, 15, 4.0)];
+
+// Number of candles used for the signal line SMA.
+static KST_SIGNAL_PERIOD: u16 = 9;
+
+#[derive(Debug)]
+pub struct Kst {
+    rocs: Vec<(Roc, MAData, f64)>,
+    latest: Option<f64>,
+    signal: MAData,
+}
+
+impl Kst {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Kst::with_components(&KST_COMPONENTS, KST_SIGNAL_PERIOD)
+    }
+
+    // Weighted sum of the given (ROC period, SMA smoothing period, weight)
+    // components, with a signal line SMA over 'signal_period'.
+    pub fn with_components(components: &[(u16, u16, f64)], signal_period: u16) -> Self {
+        Kst {
+            rocs: components
+                .iter()
+                .map(|(roc, sma, weight)| (Roc::new(*roc), MAData::new(*sma), *weight))
+                .collect(),
+            latest: None,
+            signal: MAData::new(signal_period),
+        }
+    }
+
+    // Current KST value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Current signal line value, the SMA of the KST.
+    #[allow(dead_code)]
+    pub fn signal(&self) -> Option<f64> {
+        self.signal.latest()
+    }
+
+    // Compute the latest KST value based on the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        let mut kst = 0.0;
+        let mut ready = true;
+
+        for (roc, sma, weight) in self.rocs.iter_mut() {
+            roc.compute(close_price);
+            if let Some(r) = roc.latest() {
+                sma.compute(r, false);
+            }
+
+            match sma.latest() {
+                Some(smoothed) => kst += smoothed * *weight,
+                None => ready = false,
+            }
+        }
+
+        if ready {
+            self.latest = Some(kst);
+            self.signal.compute(kst, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uptrend() {
+        let mut kst = Kst::new();
+        for i in 0..60 {
+            kst.compute(200.0 - i as f64);
+        }
+        assert!(kst.latest().unwrap() < 0.0);
+
+        let mut crossed = false;
+        let mut prev: Option<(f64, f64)> = None;
+        for i in 0..60 {
+            kst.compute(141.0 + 2.0 * i as f64);
+            if let (Some(k), Some(s)) = (kst.latest(), kst.signal()) {
+                if let Some((prev_k, prev_s)) = prev {
+                    if prev_k < prev_s && k > s {
+                        crossed = true;
+                    }
+                }
+                prev = Some((k, s));
+            }
+        }
+
+        assert!(crossed);
+        assert!(kst.latest().unwrap() > 0.0);
+    }
+}
@@ -0,0 +1,102 @@
+// Linear regression moving average (linreg.rs), the end point of the least squares
+// line through the recent closes. Lags less than an SMA, and the line's slope
+// gauges the trend.
+use crate::window;
+
+use window::RollingWindow;
+
+#[derive(Debug)]
+pub struct LinRegMa {
+    // Close prices, newest at the front.
+    acc: RollingWindow<f64>,
+    pub period: u16,
+
+    latest: Option<f64>,
+    slope: Option<f64>,
+}
+
+impl LinRegMa {
+    #[allow(dead_code)]
+    pub fn new(period: u16) -> Self {
+        LinRegMa {
+            acc: RollingWindow::new(period as usize),
+            period,
+            latest: None,
+            slope: None,
+        }
+    }
+
+    // Current end point of the regression line.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Change in the regression line per candle.
+    #[allow(dead_code)]
+    pub fn slope(&self) -> Option<f64> {
+        self.slope
+    }
+
+    // Fit the line to the window including the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if self.period < 2 || !close_price.is_finite() {
+            return;
+        }
+
+        self.acc.push(close_price);
+        if !self.acc.is_full() {
+            return;
+        }
+
+        // x runs from 0 for the oldest close to n - 1 for the newest.
+        let n = self.acc.len() as f64;
+        let sum_x = n * (n - 1.0) / 2.0;
+        let sum_xx = (n - 1.0) * n * (2.0 * n - 1.0) / 6.0;
+        let sum_y = self.acc.sum();
+        let sum_xy: f64 = self
+            .acc
+            .iter()
+            .enumerate()
+            .map(|(i, y)| (n - 1.0 - i as f64) * y)
+            .sum();
+
+        let slope = (n * sum_xy - sum_x * sum_y) / (n * sum_xx - sum_x * sum_x);
+        let intercept = (sum_y - slope * sum_x) / n;
+
+        self.slope = Some(slope);
+        self.latest = Some(intercept + slope * (n - 1.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear() {
+        let mut lsma = LinRegMa::new(10);
+        for i in 0..30 {
+            let cp = 50.0 + 2.5 * i as f64;
+            lsma.compute(cp);
+            if i < 9 {
+                assert!(lsma.latest().is_none());
+            } else {
+                assert!((lsma.latest().unwrap() - cp).abs() < 1e-9);
+                assert!((lsma.slope().unwrap() - 2.5).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn flat() {
+        let mut lsma = LinRegMa::new(5);
+        for cp in [10.0, 12.0, 10.0, 12.0, 10.0].iter() {
+            lsma.compute(*cp);
+        }
+
+        assert!(lsma.slope().unwrap().abs() < 1e-9);
+        assert!((lsma.latest().unwrap() - 10.8).abs() < 1e-9);
+    }
+}
@@ -1,49 +1,170 @@
+use crate::error;
 use crate::position;
 use crate::process_md;
+use crate::roc;
+use crate::rsi;
 use crate::tradingpair;
+use crate::window;
 
+use error::CtError;
 use position::PositionType;
 use tradingpair::TradingPair;
+use window::RollingWindow;
 
 use log::{debug, info};
 use math::round;
-use std::collections::VecDeque;
 
-#[derive(Debug)]
+// What a cross check does when, after flooring, an MA value exactly equals the
+// MA it's compared against. Without a rule a cross which touches the other MA
+// on one candle and moves through it on the next is never signalled.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum TreatEqualAs {
+    // Equal values are neither above nor below, so no signal (the default).
+    #[default]
+    NoSignal,
+    // An equal previous value takes the side of the value before it.
+    #[allow(dead_code)]
+    HoldPrevious,
+    // Compare the unrounded values instead.
+    #[allow(dead_code)]
+    UseRaw,
+}
+
+// How an EMA starts. SmaSeed waits for a full window and seeds with its SMA,
+// Immediate starts from the first close, trading accuracy for an early value.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum EmaWarmup {
+    #[default]
+    SmaSeed,
+    #[allow(dead_code)]
+    Immediate,
+}
+
+// Moving average type for indicators built out of several MAs.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum MAKind {
+    #[allow(dead_code)]
+    Sma,
+    #[default]
+    Ema,
+    // Linearly weighted, the newest close weighted highest.
+    #[allow(dead_code)]
+    Wma,
+}
+
+// Where an MA is in its warm-up after a compute, see MAData::compute_status().
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ComputeStatus {
+    // No value yet.
+    Warming,
+    // This close produced the first value.
+    JustReady,
+    // Already had a value before this close.
+    Updated,
+}
+
+// Number of MA values kept for nth_back() by default.
+static DEFAULT_HISTORY_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
 pub struct MAData {
-    latest: Option<f64>,                  // Current MA value.
-    penultimate: Option<f64>,             // Previous MA value.
-    penultimate_penultimate: Option<f64>, // Previous previous MA value.
+    // Recent MA values, newest (the current value) at the front.
+    history: RollingWindow<f64>,
 
     // MA accumulator data.
-    pub acc: VecDeque<f64>,
-    // Number of candles required before computing the average.
+    pub acc: RollingWindow<f64>,
+    // Number of candles averaged over.
     pub num_candles: u16,
+    // Number of candles required before computing the average, the average is
+    // over the candles collected until the window is full.
+    pub min_periods: u16,
+
+    // State prior to the most recent compute, used to undo it.
+    undo: Option<ComputeUndo>,
+
+    // Weights applied to the closes in place of the simple average, newest first.
+    weights: Option<Vec<f64>>,
+
+    // How the MA starts when computed as an EMA.
+    pub ema_warmup: EmaWarmup,
+
+    // Every close computed, newest first, when retained with retain_raw().
+    raw: Option<RollingWindow<f64>>,
+
+    // Drop the accumulator once the EMA is seeded, see new_compact_ema().
+    compact: bool,
 }
 
-#[derive(Debug)]
+// Everything the most recent compute overwrote or discarded.
+#[derive(Debug, Clone, Copy)]
+struct ComputeUndo {
+    // Whether a new MA value was added, and the oldest history value it discarded.
+    updated: bool,
+    history_evicted: Option<f64>,
+    evicted: Option<f64>,
+    // Whether the close was retained as raw, and the raw close it discarded.
+    raw_pushed: bool,
+    raw_evicted: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
 pub struct MACD {
+    // Fast and slow MAs, EMAs unless constructed with another kind.
     pub ema12: MAData,
     pub ema26: MAData,
     pub signal: MAData,
+    pub kind: MAKind,
     pub macd_latest: Option<f64>,
     pub macd_previous: Option<f64>,
+
+    // Candles since the MACD line last crossed the signal line.
+    since_cross: Option<u64>,
 }
 
 impl MACD {
     pub fn new() -> Self {
+        MACD::with_kind(12, 26, 9, MAKind::Ema)
+    }
+
+    // MACD over the given periods with all three MAs of the given kind.
+    pub fn with_kind(fast: u16, slow: u16, signal: u16, kind: MAKind) -> Self {
         MACD {
-            ema12: MAData::new(12),
-            ema26: MAData::new(26),
-            signal: MAData::new(9),
+            ema12: MAData::with_kind(fast, kind),
+            ema26: MAData::with_kind(slow, kind),
+            signal: MAData::with_kind(signal, kind),
+            kind,
             macd_latest: None,
             macd_previous: None,
+            since_cross: None,
         }
     }
 
+    // How far the MACD line is above (positive) or below the signal line.
+    #[allow(dead_code)]
+    pub fn distance_from_signal(&self) -> Option<f64> {
+        Some(self.macd_latest? - self.signal.latest()?)
+    }
+
+    // distance_from_signal() as of the previous candle.
+    pub fn previous_distance_from_signal(&self) -> Option<f64> {
+        Some(self.macd_previous? - self.signal.penultimate()?)
+    }
+
+    // Number of candles since the MACD line last crossed the signal line, 0 on the
+    // candle it crossed. None until the first cross.
+    #[allow(dead_code)]
+    pub fn candles_since_cross(&self) -> Option<u64> {
+        self.since_cross
+    }
+
     pub fn compute(&mut self, close_price: f64) {
-        self.ema12.compute(close_price, true);
-        self.ema26.compute(close_price, true);
+        if !close_price.is_finite() {
+            return;
+        }
+
+        let ema = self.kind == MAKind::Ema;
+        self.ema12.compute(close_price, ema);
+        self.ema26.compute(close_price, ema);
 
         if self.ema26.latest().is_some() {
             if self.macd_latest.is_some() {
@@ -52,67 +173,319 @@ impl MACD {
 
             let macd = self.ema12.latest().unwrap() - self.ema26.latest().unwrap();
             self.macd_latest = Some(macd);
-            self.signal.compute(macd, true);
+            self.signal.compute(macd, ema);
+
+            let prev_distance = self.previous_distance_from_signal();
+            if let (Some(prev), Some(distance)) = (prev_distance, self.distance_from_signal()) {
+                if (prev <= 0.0 && distance > 0.0) || (prev >= 0.0 && distance < 0.0) {
+                    self.since_cross = Some(0);
+                } else if let Some(since_cross) = self.since_cross.as_mut() {
+                    *since_cross += 1;
+                }
+            }
         }
     }
 }
 
 impl MAData {
     pub fn new(num_candles: u16) -> Self {
+        MAData::with_min_periods(num_candles, num_candles)
+    }
+
+    // MA which produces a value once 'min_periods' candles have been collected,
+    // rather than waiting for the full 'num_candles'.
+    pub fn with_min_periods(num_candles: u16, min_periods: u16) -> Self {
+        MAData::with_options(num_candles, min_periods, DEFAULT_HISTORY_DEPTH)
+    }
+
+    // MA keeping the last 'history_depth' values for nth_back(), at least 3.
+    #[allow(dead_code)]
+    pub fn with_history(num_candles: u16, history_depth: usize) -> Self {
+        MAData::with_options(num_candles, num_candles, history_depth)
+    }
+
+    // MA which, computed as an EMA, starts according to 'ema_warmup'.
+    #[allow(dead_code)]
+    pub fn with_ema_warmup(num_candles: u16, ema_warmup: EmaWarmup) -> Self {
+        let mut ma = MAData::new(num_candles);
+        ma.ema_warmup = ema_warmup;
+        ma
+    }
+
+    // EMA which stops keeping the window of closes once seeded, so holds O(1) state
+    // however long the period. It must always be computed as an EMA and std_dev()
+    // is unavailable after seeding.
+    #[allow(dead_code)]
+    pub fn new_compact_ema(num_candles: u16) -> Self {
+        let mut ma = MAData::new(num_candles);
+        ma.compact = true;
+        ma
+    }
+
+    // MA weighting the closes in the window by 'weights', newest first, normalised
+    // by their sum. One non-negative weight per candle, not all zero.
+    #[allow(dead_code)]
+    pub fn new_custom(num_candles: u16, weights: Vec<f64>) -> Result<Self, CtError> {
+        if weights.len() != num_candles as usize
+            || weights.iter().any(|w| !w.is_finite() || *w < 0.0)
+            || weights.iter().sum::<f64>() <= 0.0
+        {
+            return Err(CtError::InvalidWeights);
+        }
+
+        let mut ma = MAData::new(num_candles);
+        ma.weights = Some(weights);
+        Ok(ma)
+    }
+
+    // MA for the kind, an EMA or SMA still needs computing with the matching 'ema'
+    // flag. A WMA is computed as an SMA, with 'ema' false.
+    pub fn with_kind(num_candles: u16, kind: MAKind) -> Self {
+        match kind {
+            MAKind::Wma if num_candles > 0 => {
+                let weights = (1..=num_candles).rev().map(|w| w as f64).collect();
+                let mut ma = MAData::new(num_candles);
+                ma.weights = Some(weights);
+                ma
+            }
+            _ => MAData::new(num_candles),
+        }
+    }
+
+    fn with_options(num_candles: u16, min_periods: u16, history_depth: usize) -> Self {
         MAData {
-            acc: VecDeque::with_capacity(num_candles as usize),
-            latest: None,
-            penultimate: None,
-            penultimate_penultimate: None,
-            num_candles: num_candles,
+            history: RollingWindow::new(history_depth.max(3)),
+            acc: RollingWindow::new(num_candles as usize),
+            num_candles,
+            min_periods: min_periods.max(1).min(num_candles),
+            undo: None,
+            weights: None,
+            ema_warmup: EmaWarmup::default(),
+            raw: None,
+            compact: false,
         }
     }
 
     // Current simple moving average value.
     pub fn latest(&self) -> Option<f64> {
-        self.latest
+        self.nth_back(0)
     }
 
     // Previous simple moving average value.
     pub fn penultimate(&self) -> Option<f64> {
-        self.penultimate
+        self.nth_back(1)
     }
 
     // Previous previous simple moving average value.
     pub fn penultimate_penultimate(&self) -> Option<f64> {
-        self.penultimate_penultimate
+        self.nth_back(2)
+    }
+
+    // Current value floored to 'dps' decimal places, as the MA cross compares them.
+    pub fn latest_rounded(&self, dps: i8) -> Option<f64> {
+        self.latest().map(|v| round::floor(v, dps))
+    }
+
+    // Previous value floored to 'dps' decimal places.
+    pub fn penultimate_rounded(&self, dps: i8) -> Option<f64> {
+        self.penultimate().map(|v| round::floor(v, dps))
+    }
+
+    // Moving average value 'n' candles ago, 0 being the current value. None beyond
+    // the history depth.
+    pub fn nth_back(&self, n: usize) -> Option<f64> {
+        self.history.iter().nth(n).copied()
+    }
+
+    // Change between the current and previous moving average values.
+    pub fn slope(&self) -> Option<f64> {
+        match (self.latest(), self.penultimate()) {
+            (Some(latest), Some(penultimate)) => Some(latest - penultimate),
+            _ => None,
+        }
+    }
+
+    // Cross detection against another MA, returns:
+    // PositionType::Long if this MA crosses 'other' from below.
+    // PositionType::Short if this MA crosses 'other' from above.
+    // Values are floored to 'dps' decimal places before comparing this MA's latest
+    // and previous values with the other's latest.
+    #[allow(dead_code)]
+    pub fn cross_against(&self, other: &MAData, dps: i8) -> PositionType {
+        self.cross_against_with_ties(other, dps, TreatEqualAs::NoSignal)
+    }
+
+    // As cross_against() with 'ties' deciding what floored values which are
+    // exactly equal mean.
+    pub fn cross_against_with_ties(
+        &self,
+        other: &MAData,
+        dps: i8,
+        ties: TreatEqualAs,
+    ) -> PositionType {
+        if let (Some(raw_latest), Some(raw_prev), Some(raw_other_latest)) =
+            (self.latest(), self.penultimate(), other.latest())
+        {
+            let mut latest = self.latest_rounded(dps).unwrap();
+            let mut prev = self.penultimate_rounded(dps).unwrap();
+            let mut other_latest = other.latest_rounded(dps).unwrap();
+
+            match ties {
+                TreatEqualAs::NoSignal => {}
+                TreatEqualAs::HoldPrevious => {
+                    if prev == other_latest {
+                        if let Some(prev_prev) = self.penultimate_penultimate() {
+                            prev = round::floor(prev_prev, dps);
+                        }
+                    }
+                }
+                TreatEqualAs::UseRaw => {
+                    if latest == other_latest || prev == other_latest {
+                        latest = raw_latest;
+                        prev = raw_prev;
+                        other_latest = raw_other_latest;
+                    }
+                }
+            }
+
+            if latest > other_latest && prev < other_latest {
+                return PositionType::Long;
+            } else if latest < other_latest && prev > other_latest {
+                return PositionType::Short;
+            }
+        }
+
+        PositionType::None
+    }
+
+    // Population standard deviation of the closes in the current window, only
+    // available once the window is full.
+    #[allow(dead_code)]
+    pub fn std_dev(&self) -> Option<f64> {
+        if !self.acc.is_full() {
+            return None;
+        }
+
+        let n = self.acc.len() as f64;
+        let mean = self.acc.sum() / n;
+        let variance = self.acc.iter().map(|cp| (cp - mean).powi(2)).sum::<f64>() / n;
+
+        Some(variance.sqrt())
     }
 
     // Set new moving average value and make the old current
     // the penultimate.
-    fn update(&mut self, new_ma: f64) {
-        self.penultimate_penultimate = self.penultimate;
-        self.penultimate = self.latest;
-        self.latest = Some(new_ma);
+    fn update(&mut self, new_ma: f64) -> Option<f64> {
+        self.history.push(new_ma)
+    }
+
+    // Keep up to 'max_closes' of the raw closes computed from now on, so the MA
+    // can be rebuilt with different parameters by recompute_from_raw().
+    #[allow(dead_code)]
+    pub fn retain_raw(&mut self, max_closes: usize) {
+        self.raw = Some(RollingWindow::new(max_closes));
+    }
+
+    // Rebuild the MA over 'num_candles' from the retained raw closes, as if it had
+    // been created with that period and fed them. Without retained closes the MA
+    // restarts empty. Custom weights are dropped if they no longer fit the period.
+    #[allow(dead_code)]
+    pub fn recompute_from_raw(&mut self, num_candles: u16, ema: bool) {
+        // A full window stays required unless fewer periods were asked for.
+        let min_periods = if self.min_periods == self.num_candles {
+            num_candles
+        } else {
+            self.min_periods
+        };
+        let mut rebuilt = MAData::with_options(num_candles, min_periods, self.history.cap());
+        rebuilt.ema_warmup = self.ema_warmup;
+        rebuilt.compact = self.compact;
+        rebuilt.weights = self
+            .weights
+            .take()
+            .filter(|w| w.len() == num_candles as usize);
+
+        if let Some(raw) = self.raw.take() {
+            for cp in raw.iter().rev() {
+                rebuilt.compute(*cp, ema);
+            }
+            rebuilt.raw = Some(raw);
+        }
+        // The rebuild didn't retain the closes it replayed, so it can't be undone.
+        rebuilt.undo = None;
+
+        *self = rebuilt;
+    }
+
+    // As compute() but reporting whether the MA just finished warming up. A
+    // rejected close reports the state the MA was already in.
+    #[allow(dead_code)]
+    pub fn compute_status(&mut self, close_price: f64, ema: bool) -> ComputeStatus {
+        let was_ready = self.latest().is_some();
+        self.compute(close_price, ema);
+
+        match (was_ready, self.latest().is_some()) {
+            (true, _) => ComputeStatus::Updated,
+            (false, true) => ComputeStatus::JustReady,
+            (false, false) => ComputeStatus::Warming,
+        }
     }
 
     // Compute the latest moving average value based on the close price.
     pub fn compute(&mut self, close_price: f64, ema: bool) {
-        if self.num_candles == 0 {
+        // A NaN or inf close would poison the sum for as long as it's in the window.
+        if self.num_candles == 0 || !close_price.is_finite() {
             return;
         }
 
-        if self.acc.len() == self.num_candles as usize {
-            // Discard the oldest close price we saved.
-            self.acc.pop_back();
+        let (raw_pushed, raw_evicted) = match self.raw.as_mut() {
+            Some(raw) => (true, raw.push(close_price)),
+            None => (false, None),
+        };
+
+        if self.compact && ema {
+            if let Some(prev_ema) = self.latest() {
+                // Seeded, the EMA only depends on its previous value from here.
+                if self.acc.cap() > 0 {
+                    self.acc = RollingWindow::new(0);
+                }
+                let weight = 2.0 / (self.num_candles as f64 + 1.0);
+                let ema = (close_price * weight) + (prev_ema * (1.0 - weight));
+                self.undo = Some(ComputeUndo {
+                    updated: true,
+                    history_evicted: self.update(ema),
+                    evicted: None,
+                    raw_pushed,
+                    raw_evicted,
+                });
+                return;
+            }
         }
 
-        // Add the newest close price to the accumulator vector.
-        self.acc.push_front(close_price);
-        if self.acc.len() == self.num_candles as usize {
-            // We've got enough data to compute the MA.
-            let mut acc_val = 0.0;
+        // Add the newest close price to the accumulator, discarding the oldest
+        // close price we saved once the window is full.
+        let evicted = self.acc.push(close_price);
 
-            for cp in self.acc.iter() {
-                acc_val += cp;
-            }
+        let mut undo = ComputeUndo {
+            updated: false,
+            history_evicted: None,
+            evicted,
+            raw_pushed,
+            raw_evicted,
+        };
 
-            let new_ma = acc_val / self.num_candles as f64;
+        // An immediate EMA starts from the first close, the average of a window of
+        // one.
+        let immediate = ema && self.ema_warmup == EmaWarmup::Immediate;
+        if immediate || self.acc.len() >= self.min_periods as usize {
+            // We've got enough data to compute the MA.
+            let new_ma = match &self.weights {
+                Some(weights) => {
+                    let weighted: f64 = self.acc.iter().zip(weights).map(|(cp, w)| cp * w).sum();
+                    weighted / weights.iter().take(self.acc.len()).sum::<f64>()
+                }
+                None => self.acc.sum() / self.acc.len() as f64,
+            };
 
             if ema {
                 let prev_ema = match self.latest() {
@@ -124,14 +497,264 @@ impl MAData {
                 // https://www.investopedia.com/ask/answers/122314/what-exponential-moving-average-ema-formula-and-how-ema-calculated.asp
                 let weight = 2.0 / (self.num_candles as f64 + 1.0);
                 let ema = (close_price * weight) + (prev_ema * (1.0 - weight));
-                self.update(ema);
+                undo.history_evicted = self.update(ema);
             } else {
-                self.update(new_ma);
+                undo.history_evicted = self.update(new_ma);
+            }
+            undo.updated = true;
+        }
+
+        self.undo = Some(undo);
+    }
+
+    // Undo the most recent compute and apply a corrected close price in its place,
+    // for feeds which revise the last closed candle. The state overwritten by the
+    // last compute is kept, so this is exact for both SMA and EMA. Only the most
+    // recent candle can be replaced.
+    #[allow(dead_code)]
+    pub fn replace_last(&mut self, corrected_close: f64, ema: bool) {
+        if !corrected_close.is_finite() {
+            return;
+        }
+
+        // Nothing to replace.
+        let undo = match self.undo.take() {
+            Some(undo) => undo,
+            None => return,
+        };
+
+        self.acc.pop_newest();
+        if let Some(evicted) = undo.evicted {
+            self.acc.push_oldest(evicted);
+        }
+
+        if undo.updated {
+            self.history.pop_newest();
+            if let Some(evicted) = undo.history_evicted {
+                self.history.push_oldest(evicted);
+            }
+        }
+
+        if undo.raw_pushed {
+            if let Some(raw) = self.raw.as_mut() {
+                raw.pop_newest();
+                if let Some(evicted) = undo.raw_evicted {
+                    raw.push_oldest(evicted);
+                }
             }
         }
+
+        self.compute(corrected_close, ema);
+    }
+}
+
+// An indicator computed from one value per candle, whose output can be smoothed
+// by feeding it into an MA, MAData takes any f64 stream so this is just:
+//
+//     rsi.compute(close_price);
+//     if let Some(rsi) = rsi.latest() {
+//         rsi_ma.compute(rsi, ema);
+//     }
+//
+// SmoothedIndicator wraps that up for any IndicatorSource.
+pub trait IndicatorSource {
+    fn compute(&mut self, value: f64);
+    fn latest(&self) -> Option<f64>;
+}
+
+impl IndicatorSource for rsi::Rsi {
+    fn compute(&mut self, value: f64) {
+        rsi::Rsi::compute(self, value)
+    }
+
+    fn latest(&self) -> Option<f64> {
+        rsi::Rsi::latest(self)
     }
 }
 
+impl IndicatorSource for roc::Roc {
+    fn compute(&mut self, value: f64) {
+        roc::Roc::compute(self, value)
+    }
+
+    fn latest(&self) -> Option<f64> {
+        roc::Roc::latest(self)
+    }
+}
+
+// The MACD's output is its histogram, the MACD line less the signal line.
+impl IndicatorSource for MACD {
+    fn compute(&mut self, value: f64) {
+        MACD::compute(self, value)
+    }
+
+    fn latest(&self) -> Option<f64> {
+        self.distance_from_signal()
+    }
+}
+
+// An indicator with an MA of its output.
+#[derive(Debug, Clone)]
+pub struct SmoothedIndicator<I: IndicatorSource> {
+    pub indicator: I,
+    pub ma: MAData,
+    // Exponential or simple MA.
+    pub ema: bool,
+}
+
+impl<I: IndicatorSource> SmoothedIndicator<I> {
+    #[allow(dead_code)]
+    pub fn new(indicator: I, num_candles: u16, ema: bool) -> Self {
+        SmoothedIndicator {
+            indicator,
+            ma: MAData::new(num_candles),
+            ema,
+        }
+    }
+
+    // Current MA of the indicator.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.ma.latest()
+    }
+
+    // Update the indicator, then the MA with its output once it has one.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+
+        self.indicator.compute(value);
+        if let Some(output) = self.indicator.latest() {
+            self.ma.compute(output, self.ema);
+        }
+    }
+}
+
+// A set of moving averages over different periods, ordered fastest first.
+#[derive(Debug)]
+pub struct MaFan {
+    pub mas: Vec<MAData>,
+    // Exponential or simple MAs.
+    pub ema: bool,
+}
+
+impl MaFan {
+    #[allow(dead_code)]
+    pub fn new(periods: &[u16], ema: bool) -> Self {
+        let mut periods = periods.to_vec();
+        periods.sort_unstable();
+
+        MaFan {
+            mas: periods.into_iter().map(MAData::new).collect(),
+            ema,
+        }
+    }
+
+    // Guppy multiple moving average short term group, the traders' EMAs.
+    #[allow(dead_code)]
+    pub fn guppy_short() -> Self {
+        MaFan::new(&GMMA_SHORT_PERIODS, true)
+    }
+
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        for ma in self.mas.iter_mut() {
+            ma.compute(close_price, self.ema);
+        }
+    }
+
+    // Distance between the highest and lowest MA as a percentage of the slowest.
+    #[allow(dead_code)]
+    pub fn separation_pct(&self) -> Option<f64> {
+        fan_separation_pct(self.mas.iter().map(|ma| ma.latest()))
+    }
+
+    // Separation as of the previous value.
+    #[allow(dead_code)]
+    pub fn previous_separation_pct(&self) -> Option<f64> {
+        fan_separation_pct(self.mas.iter().map(|ma| ma.penultimate()))
+    }
+
+    // Whether the MAs are converging or spreading apart.
+    #[allow(dead_code)]
+    pub fn compression_state(&self) -> CompressionState {
+        match (self.separation_pct(), self.previous_separation_pct()) {
+            (Some(now), Some(prev)) if now < prev => CompressionState::Compressing,
+            (Some(now), Some(prev)) if now > prev => CompressionState::Expanding,
+            _ => CompressionState::Neutral,
+        }
+    }
+
+    // Trend onset, the fan spreading out from a separation of at most
+    // max_compressed_pct with every MA stacked in the same direction.
+    #[allow(dead_code)]
+    pub fn expansion_signal(&self, max_compressed_pct: f64) -> PositionType {
+        let was_compressed = self
+            .previous_separation_pct()
+            .is_some_and(|prev| prev <= max_compressed_pct);
+        if !was_compressed || self.compression_state() != CompressionState::Expanding {
+            return PositionType::None;
+        }
+
+        let score = ribbon_score(self);
+        if score == 1.0 {
+            PositionType::Long
+        } else if score == -1.0 {
+            PositionType::Short
+        } else {
+            PositionType::None
+        }
+    }
+}
+
+static GMMA_SHORT_PERIODS: [u16; 6] = [3, 5, 8, 10, 12, 15];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CompressionState {
+    Compressing,
+    Expanding,
+    Neutral,
+}
+
+fn fan_separation_pct(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    let values: Option<Vec<f64>> = values.collect();
+    let values = values?;
+    let slowest = *values.last()?;
+    if slowest == 0.0 {
+        return None;
+    }
+
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    Some((max - min) / slowest.abs() * 100.0)
+}
+
+// Trend strength from -1.0 to 1.0 based on the order of adjacent MAs in the fan,
+// each faster MA above its slower neighbour adds to the score and each below takes
+// from it. 1.0 when the fan is fully stacked bullish, -1.0 when fully inverted.
+// Pairs without values yet count as neither.
+#[allow(dead_code)]
+pub fn ribbon_score(fan: &MaFan) -> f64 {
+    if fan.mas.len() < 2 {
+        return 0.0;
+    }
+
+    let mut score = 0.0;
+    for pair in fan.mas.windows(2) {
+        if let (Some(faster), Some(slower)) = (pair[0].latest(), pair[1].latest()) {
+            if faster > slower {
+                score += 1.0;
+            } else if faster < slower {
+                score -= 1.0;
+            }
+        }
+    }
+
+    score / (fan.mas.len() - 1) as f64
+}
+
 // MACD crossing signal line.
 pub fn trading_decision_macd(
     tp: &TradingPair,
@@ -209,20 +832,21 @@ pub fn trading_decision_macd(
 // Trend reversal detection, returns:
 // PositionType::Long if the fast ma starts to trend upwards.
 // PositionType::Short if the fast ma starts to trend downwards.
+// With a trend_change_min_pct the ma must move at least that percentage into and
+// out of the turn, so wiggles in a flat ma aren't reversals.
 pub fn trading_decision_ma_trend_change(
     tp: &TradingPair,
     mt: &process_md::MarketDataTracker,
     closing_price: f64,
 ) -> PositionType {
-    if mt.slow_ma_data.latest().is_none()
-        || mt.fast_ma_data.latest().is_none()
+    if mt.fast_ma_data.latest().is_none()
         || mt.fast_ma_data.penultimate().is_none()
         || mt.fast_ma_data.penultimate_penultimate().is_none()
     {
         return PositionType::None;
     }
 
-    let c = mt.slow_ma_data.latest().unwrap();
+    let c = mt.fast_ma_data.latest().unwrap();
     let p = mt.fast_ma_data.penultimate().unwrap();
     let pp = mt.fast_ma_data.penultimate_penultimate().unwrap();
 
@@ -235,7 +859,8 @@ pub fn trading_decision_ma_trend_change(
         c,
     );
 
-    if c > p && p < pp {
+    let min_move = p.abs() * mt.trend_change_min_pct.unwrap_or(0.0) / 100.0;
+    if c - p > min_move && pp - p > min_move {
         info!(
                 "[BUY][TREND] {}, close: {}, signal: FMA({}) > FMA_PREV({}) and FMA_PREV({}) < FMA_PREV_PREV({})",
                 tp.symbol(),
@@ -247,7 +872,7 @@ pub fn trading_decision_ma_trend_change(
             );
 
         return PositionType::Long;
-    } else if c < p && p > pp {
+    } else if p - c > min_move && p - pp > min_move {
         info!(
                 "[SELL][TREND] {}, close: {}, signal: FMA({}) < FMA_PREV({}) and FMA_PREV({}) > FMA_PREV_PREV({})",
                 tp.symbol(),
@@ -278,45 +903,587 @@ pub fn trading_decision_ma_cross(
     {
         // We have data to make a decision.
         let dps = tp.get_price_dps();
-        let f_ma_latest_val = round::floor(mt.fast_ma_data.latest().unwrap(), dps);
-        let f_ma_prev_val = round::floor(mt.fast_ma_data.penultimate().unwrap(), dps);
-        let s_ma_latest_val = round::floor(mt.slow_ma_data.latest().unwrap(), dps);
+        let f_ma_latest_val = mt.fast_ma_data.latest_rounded(dps).unwrap();
+        let f_ma_prev_val = mt.fast_ma_data.penultimate_rounded(dps).unwrap();
+        let s_ma_latest_val = mt.slow_ma_data.latest_rounded(dps).unwrap();
 
         debug!(
-            "[MA][CROSS] {:#?} CLOSE({}) FMA({}) SMA({})",
+            "[MA][CROSS] {} CLOSE({}) FMA({}) SMA({})",
             tp.symbol(),
             closing_price,
             f_ma_latest_val,
             s_ma_latest_val,
         );
 
-        if f_ma_latest_val > s_ma_latest_val && f_ma_prev_val < s_ma_latest_val {
+        let decision =
+            mt.fast_ma_data
+                .cross_against_with_ties(&mt.slow_ma_data, dps, mt.ma_tie_policy);
+        if decision == PositionType::Long {
             // Fast moving average is above the slow moving average
             info!(
-                "[BUY][CROSS] {:#?}, close: {}, signal: FMA({}) > SMA({} > FMA_PREV({})",
+                "[BUY][CROSS] {}, close: {}, signal: FMA({}) > SMA({} > FMA_PREV({})",
                 tp.symbol(),
                 closing_price,
                 f_ma_latest_val,
                 s_ma_latest_val,
                 f_ma_prev_val,
             );
-
-            return PositionType::Long;
-        } else if f_ma_latest_val < s_ma_latest_val && f_ma_prev_val > s_ma_latest_val {
+        } else if decision == PositionType::Short {
             // Fast moving average is below the slow moving average.
             info!(
-                "[SELL][CROSS] {:#?}, close: {}, signal: FMA({}) < SMA({}) < FMA_PREV({})",
+                "[SELL][CROSS] {}, close: {}, signal: FMA({}) < SMA({}) < FMA_PREV({})",
                 tp.symbol(),
                 closing_price,
                 f_ma_latest_val,
                 s_ma_latest_val,
                 f_ma_prev_val,
             );
-
-            return PositionType::Short;
         }
+
+        return decision;
     }
 
     // No signal indicated or no change detected.
     return PositionType::None;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trend_change_min_pct() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let decisions = |closes: &[f64], min_pct: Option<f64>| -> Vec<PositionType> {
+            let mut mt = process_md::MarketDataTracker::new(
+                process_md::TradeSignal::MaTrendReversal,
+                None,
+                Some(1),
+                false,
+            );
+            mt.trend_change_min_pct = min_pct;
+            closes
+                .iter()
+                .map(|cp| {
+                    mt.compute(*cp);
+                    trading_decision_ma_trend_change(&tp, &mt, *cp)
+                })
+                .collect()
+        };
+
+        let wiggle = [100.0, 100.001, 100.0, 100.001];
+        assert!(decisions(&wiggle, None).contains(&PositionType::Long));
+        assert!(decisions(&wiggle, Some(0.5))
+            .iter()
+            .all(|d| *d == PositionType::None));
+
+        let v = [100.0, 98.0, 96.0, 98.0];
+        assert_eq!(decisions(&v, Some(0.5))[3], PositionType::Long);
+        let peak = [96.0, 98.0, 100.0, 98.0];
+        assert_eq!(decisions(&peak, Some(0.5))[3], PositionType::Short);
+    }
+
+    #[test]
+    fn trend_change_fast_ma() {
+        // The trend strategy only has a fast MA, comparing against the slow MA
+        // meant it never fired.
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let last_decision = |closes: &[f64]| {
+            let mut mt = process_md::MarketDataTracker::new(
+                process_md::TradeSignal::MaTrendReversal,
+                None,
+                Some(1),
+                false,
+            );
+            let mut last = PositionType::None;
+            for cp in closes.iter() {
+                mt.compute(*cp);
+                last = trading_decision_ma_trend_change(&tp, &mt, *cp);
+            }
+            last
+        };
+
+        assert_eq!(
+            last_decision(&[100.0, 98.0, 96.0, 98.0]),
+            PositionType::Long
+        );
+        assert_eq!(
+            last_decision(&[96.0, 98.0, 100.0, 98.0]),
+            PositionType::Short
+        );
+        assert_eq!(
+            last_decision(&[96.0, 98.0, 100.0, 102.0]),
+            PositionType::None
+        );
+    }
+
+    #[test]
+    fn latest_rounded() {
+        let mut fast = MAData::new(1);
+        let mut slow = MAData::new(1);
+        assert_eq!(fast.latest_rounded(2), None);
+
+        fast.compute(10.0, false);
+        fast.compute(10.019, false);
+        slow.compute(10.011, false);
+        assert_eq!(fast.latest_rounded(2), Some(10.01));
+        assert_eq!(fast.penultimate_rounded(2), Some(10.0));
+        assert_eq!(slow.latest_rounded(2), Some(10.01));
+
+        // Raw the fast MA crossed above, floored they're equal so the cross agrees
+        // with the rounded values and there's no signal.
+        assert_eq!(fast.cross_against(&slow, 2), PositionType::None);
+        assert_eq!(fast.cross_against(&slow, 3), PositionType::Long);
+    }
+
+    #[test]
+    fn std_dev() {
+        let mut ma = MAData::new(8);
+        for cp in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0].iter() {
+            ma.compute(*cp, false);
+            assert!(ma.std_dev().is_none());
+        }
+
+        ma.compute(9.0, false);
+        assert_eq!(ma.std_dev(), Some(2.0));
+    }
+
+    #[test]
+    fn macd_since_cross() {
+        let mut macd = MACD::new();
+        for _ in 0..40 {
+            macd.compute(100.0);
+        }
+        assert_eq!(macd.distance_from_signal(), Some(0.0));
+        assert_eq!(macd.candles_since_cross(), None);
+
+        // A rally pulls the MACD line up through the signal line and away from it.
+        let mut distances = vec![];
+        for i in 1..6 {
+            macd.compute(100.0 + i as f64);
+            assert_eq!(macd.candles_since_cross(), Some(i - 1));
+            distances.push(macd.distance_from_signal().unwrap());
+        }
+        assert!(distances[0] > 0.0);
+        assert!(distances.windows(2).all(|w| w[1] > w[0]));
+
+        // Then a sharp drop crosses back down.
+        macd.compute(90.0);
+        assert_eq!(macd.candles_since_cross(), Some(0));
+        assert!(macd.distance_from_signal().unwrap() < 0.0);
+    }
+
+    #[test]
+    fn ema_warmup() {
+        let mut seeded = MAData::new(10);
+        let mut immediate = MAData::with_ema_warmup(10, EmaWarmup::Immediate);
+
+        immediate.compute(50.0, true);
+        seeded.compute(50.0, true);
+        assert_eq!(immediate.latest(), Some(50.0));
+        assert_eq!(seeded.latest(), None);
+
+        let mut diffs = vec![];
+        for i in 0..100 {
+            let cp = 100.0 + ((i * 7) % 5) as f64;
+            immediate.compute(cp, true);
+            seeded.compute(cp, true);
+            if let Some(seeded) = seeded.latest() {
+                diffs.push((immediate.latest().unwrap() - seeded).abs());
+            }
+        }
+        assert!(diffs[0] > 1.0);
+        assert!(diffs[diffs.len() - 1] < 1e-6);
+    }
+
+    #[test]
+    fn recompute_from_raw() {
+        let closes: Vec<f64> = (0..30).map(|i| 100.0 + ((i * 13) % 7) as f64).collect();
+
+        let mut ma = MAData::new(5);
+        ma.retain_raw(100);
+        for cp in closes.iter() {
+            ma.compute(*cp, true);
+        }
+
+        ma.recompute_from_raw(9, true);
+        let mut fresh = MAData::new(9);
+        for cp in closes.iter() {
+            fresh.compute(*cp, true);
+        }
+        assert_eq!(ma.num_candles, 9);
+        assert_eq!(ma.latest(), fresh.latest());
+        assert_eq!(ma.penultimate(), fresh.penultimate());
+
+        // Carries on from the rebuilt state.
+        ma.compute(104.0, true);
+        fresh.compute(104.0, true);
+        assert_eq!(ma.latest(), fresh.latest());
+
+        // Nothing retained, nothing to rebuild from.
+        let mut ma = MAData::new(5);
+        for cp in closes.iter() {
+            ma.compute(*cp, false);
+        }
+        ma.recompute_from_raw(3, false);
+        assert_eq!(ma.latest(), None);
+    }
+
+    #[test]
+    fn acc_capacity() {
+        // The oldest close is evicted before the newest is added, so a full window
+        // never needs more room.
+        let mut ma = MAData::new(20);
+        for i in 0..20 {
+            ma.compute(100.0 + i as f64, false);
+        }
+        let capacity = ma.acc.capacity();
+        assert!(capacity >= 20);
+
+        for i in 0..1000 {
+            ma.compute(100.0 + (i % 17) as f64, i % 2 == 0);
+            assert_eq!(ma.acc.capacity(), capacity);
+            assert_eq!(ma.acc.len(), 20);
+        }
+    }
+
+    #[test]
+    fn smoothed_indicator() {
+        let closes: Vec<f64> = (0..40).map(|i| 100.0 + ((i * 11) % 7) as f64).collect();
+
+        let mut smoothed = SmoothedIndicator::new(rsi::Rsi::new(14), 3, false);
+        let mut rsi = rsi::Rsi::new(14);
+        let mut rsi_values = vec![];
+        for cp in closes.iter() {
+            smoothed.compute(*cp);
+            rsi.compute(*cp);
+            if let Some(value) = rsi.latest() {
+                rsi_values.push(value);
+            }
+
+            if rsi_values.len() < 3 {
+                assert_eq!(smoothed.latest(), None);
+            } else {
+                let expected = rsi_values[rsi_values.len() - 3..].iter().sum::<f64>() / 3.0;
+                assert!((smoothed.latest().unwrap() - expected).abs() < 1e-9);
+            }
+        }
+        assert!(smoothed.latest().is_some());
+
+        // The MACD histogram smooths the same way.
+        let mut histogram = SmoothedIndicator::new(MACD::new(), 5, true);
+        for cp in closes.iter() {
+            histogram.compute(*cp);
+        }
+        assert!(histogram.latest().is_some());
+    }
+
+    #[test]
+    fn new_custom() {
+        let closes = [3.0, 5.0, 4.0, 8.0, 6.0, 7.0, 2.0];
+
+        let mut sma = MAData::new(4);
+        let mut uniform = MAData::new_custom(4, vec![0.5; 4]).unwrap();
+        // Triangular kernel, the newest close weighted highest, is the WMA.
+        let mut triangular = MAData::new_custom(4, vec![4.0, 3.0, 2.0, 1.0]).unwrap();
+        for (i, cp) in closes.iter().enumerate() {
+            sma.compute(*cp, false);
+            uniform.compute(*cp, false);
+            triangular.compute(*cp, false);
+            assert_eq!(uniform.latest(), sma.latest());
+
+            if i >= 3 {
+                let wma =
+                    (4.0 * closes[i] + 3.0 * closes[i - 1] + 2.0 * closes[i - 2] + closes[i - 3])
+                        / 10.0;
+                assert!((triangular.latest().unwrap() - wma).abs() < 1e-9);
+            }
+        }
+
+        assert!(MAData::new_custom(4, vec![1.0; 3]).is_err());
+        assert!(MAData::new_custom(2, vec![1.0, -1.0]).is_err());
+        assert!(MAData::new_custom(2, vec![0.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn cross_against_with_ties() {
+        let run = |ties: TreatEqualAs| -> Vec<PositionType> {
+            let mut fast = MAData::new(1);
+            let mut slow = MAData::new(1);
+            // The fast MA touches the slow MA, once floored, then moves through it.
+            [(9.0, 10.2), (10.4, 10.2), (11.0, 10.2)]
+                .iter()
+                .map(|(f, s)| {
+                    fast.compute(*f, false);
+                    slow.compute(*s, false);
+                    fast.cross_against_with_ties(&slow, 0, ties)
+                })
+                .collect()
+        };
+
+        let none = PositionType::None;
+        let long = PositionType::Long;
+        assert_eq!(run(TreatEqualAs::NoSignal), vec![none, none, none]);
+        assert_eq!(run(TreatEqualAs::HoldPrevious), vec![none, none, long]);
+        assert_eq!(run(TreatEqualAs::UseRaw), vec![none, long, none]);
+    }
+
+    #[test]
+    fn cross_against() {
+        let mut slow = MAData::new(4);
+        let mut fast = MAData::new(2);
+        for cp in [13.0, 12.0, 11.0, 10.0, 9.0, 8.0].iter() {
+            slow.compute(*cp, false);
+            fast.compute(*cp, false);
+            assert_eq!(fast.cross_against(&slow, 2), PositionType::None);
+        }
+
+        // Fast goes from 8.5 to 10.5 against a slow of 10.
+        slow.compute(13.0, false);
+        fast.compute(13.0, false);
+        assert_eq!(fast.cross_against(&slow, 2), PositionType::Long);
+
+        // Fast goes from 10.5 to 8 against a slow of 8.25.
+        slow.compute(3.0, false);
+        fast.compute(3.0, false);
+        assert_eq!(fast.cross_against(&slow, 2), PositionType::Short);
+    }
+
+    #[test]
+    fn replace_last() {
+        for ema in [false, true].iter() {
+            let closes = [3.0, 4.0, 5.0, 6.0, 7.0, 6.0, 5.0];
+            let mut corrected = MAData::new(4);
+            let mut correct = MAData::new(4);
+            for cp in closes.iter() {
+                corrected.compute(*cp, *ema);
+                correct.compute(*cp, *ema);
+            }
+
+            corrected.compute(100.0, *ema);
+            corrected.replace_last(4.0, *ema);
+            correct.compute(4.0, *ema);
+
+            assert_eq!(corrected.acc, correct.acc);
+            assert_eq!(corrected.latest(), correct.latest());
+            assert_eq!(corrected.penultimate(), correct.penultimate());
+            assert_eq!(
+                corrected.penultimate_penultimate(),
+                correct.penultimate_penultimate()
+            );
+        }
+
+        // Nothing computed yet, nothing to replace.
+        let mut ma = MAData::new(1);
+        ma.replace_last(4.0, false);
+        assert_eq!(ma.latest(), None);
+    }
+
+    #[test]
+    fn replace_last_raw() {
+        let closes = [3.0, 4.0, 5.0, 6.0, 7.0, 6.0, 5.0];
+        let mut ma = MAData::new(4);
+        ma.retain_raw(5);
+        for cp in closes.iter() {
+            ma.compute(*cp, true);
+        }
+        ma.compute(100.0, true);
+        ma.replace_last(4.0, true);
+        ma.recompute_from_raw(3, true);
+
+        let mut fresh = MAData::new(3);
+        for cp in closes[3..].iter().chain([4.0].iter()) {
+            fresh.compute(*cp, true);
+        }
+        assert_eq!(ma.latest(), fresh.latest());
+        assert_eq!(ma.penultimate(), fresh.penultimate());
+    }
+
+    #[test]
+    fn compact_ema() {
+        let mut windowed = MAData::new(50);
+        let mut compact = MAData::new_compact_ema(50);
+        for i in 0..200 {
+            let cp = 100.0 + 10.0 * (i as f64 / 7.0).sin();
+            windowed.compute(cp, true);
+            compact.compute(cp, true);
+            assert_eq!(compact.latest(), windowed.latest());
+        }
+        assert_eq!(compact.acc.cap(), 0);
+
+        windowed.compute(1000.0, true);
+        compact.compute(1000.0, true);
+        windowed.replace_last(99.0, true);
+        compact.replace_last(99.0, true);
+        assert_eq!(compact.latest(), windowed.latest());
+        assert_eq!(compact.penultimate(), windowed.penultimate());
+    }
+
+    #[test]
+    fn wma_macd() {
+        let mut ema = MACD::new();
+        let mut wma = MACD::with_kind(12, 26, 9, MAKind::Wma);
+        let closes: Vec<f64> = (0..120)
+            .map(|i| 100.0 + 10.0 * (i as f64 / 8.0).sin())
+            .collect();
+
+        let mut crosses = 0;
+        for cp in closes.iter() {
+            ema.compute(*cp);
+            wma.compute(*cp);
+
+            if wma.candles_since_cross() == Some(0) {
+                crosses += 1;
+                let prev = wma.previous_distance_from_signal().unwrap();
+                let distance = wma.distance_from_signal().unwrap();
+                assert!(prev.signum() != distance.signum() || prev == 0.0);
+            }
+        }
+
+        assert!(crosses > 0);
+        assert!((ema.macd_latest.unwrap() - wma.macd_latest.unwrap()).abs() > 1e-6);
+        assert_eq!(MACD::new().kind, MAKind::Ema);
+    }
+
+    #[test]
+    fn non_finite() {
+        let mut ma = MAData::new(3);
+        let mut macd = MACD::new();
+        let closes = [1.0, 2.0, f64::NAN, 3.0, f64::INFINITY, 4.0];
+        for cp in closes.iter() {
+            ma.compute(*cp, false);
+            macd.compute(*cp);
+        }
+
+        assert_eq!(ma.latest(), Some(3.0));
+        assert_eq!(ma.penultimate(), Some(2.0));
+        assert!(ma.acc.sum().is_finite());
+        assert_eq!(macd.ema12.acc.len(), 4);
+    }
+
+    #[test]
+    fn min_periods() {
+        for ema in [false, true].iter() {
+            let mut ma = MAData::with_min_periods(3, 1);
+            ma.compute(3.0, *ema);
+            assert_eq!(ma.latest(), Some(3.0));
+
+            ma.compute(5.0, *ema);
+            if !*ema {
+                assert_eq!(ma.latest(), Some(4.0));
+            }
+
+            ma.compute(7.0, *ema);
+            ma.compute(9.0, *ema);
+            if !*ema {
+                assert_eq!(ma.latest(), Some(7.0));
+            }
+        }
+
+        let mut ma = MAData::new(3);
+        ma.compute(3.0, false);
+        ma.compute(5.0, false);
+        assert_eq!(ma.latest(), None);
+        assert_eq!(ma.min_periods, 3);
+    }
+
+    #[test]
+    fn ribbon_score() {
+        let periods = [3, 5, 8, 10, 12, 15, 18, 20];
+
+        let mut fan = MaFan::new(&periods, true);
+        assert_eq!(super::ribbon_score(&fan), 0.0);
+        for i in 0..40 {
+            fan.compute(100.0 + i as f64);
+        }
+        assert_eq!(super::ribbon_score(&fan), 1.0);
+
+        let mut fan = MaFan::new(&periods, true);
+        for i in 0..40 {
+            fan.compute(100.0 - i as f64);
+        }
+        assert_eq!(super::ribbon_score(&fan), -1.0);
+
+        // Two pairs bullish, two bearish.
+        let mut fan = MaFan::new(&[1, 2, 3, 4, 5], false);
+        for (ma, v) in fan.mas.iter_mut().zip([4.0, 3.0, 2.0, 3.0, 4.0].iter()) {
+            ma.update(*v);
+        }
+        assert_eq!(super::ribbon_score(&fan), 0.0);
+
+        fan.mas[4].update(1.0);
+        assert_eq!(super::ribbon_score(&fan), 0.5);
+    }
+
+    #[test]
+    fn guppy_compression() {
+        let mut fan = MaFan::guppy_short();
+        assert_eq!(fan.compression_state(), CompressionState::Neutral);
+
+        // A downtrend spreads the fan out, then a flat market pulls it together.
+        for i in 0..30 {
+            fan.compute(130.0 - i as f64);
+        }
+        fan.compute(105.0);
+        assert_eq!(fan.compression_state(), CompressionState::Compressing);
+        for _ in 0..60 {
+            fan.compute(105.0);
+            assert_ne!(fan.compression_state(), CompressionState::Expanding);
+            assert_eq!(fan.expansion_signal(0.1), PositionType::None);
+        }
+        assert!(fan.separation_pct().unwrap() < 0.1);
+
+        // Breaking out upwards.
+        fan.compute(106.0);
+        assert_eq!(fan.compression_state(), CompressionState::Expanding);
+        assert_eq!(fan.expansion_signal(0.1), PositionType::Long);
+
+        // Still expanding but no longer from a compressed fan.
+        for i in 2..10 {
+            fan.compute(105.0 + i as f64);
+        }
+        assert_eq!(fan.compression_state(), CompressionState::Expanding);
+        assert_eq!(fan.expansion_signal(0.1), PositionType::None);
+    }
+
+    #[test]
+    fn compute_status() {
+        for ema in [false, true].iter() {
+            let mut ma = MAData::new(3);
+            for (i, cp) in [1.0, 2.0, f64::NAN, 3.0, 4.0, 5.0].iter().enumerate() {
+                let was_ready = ma.latest().is_some();
+                let status = ma.compute_status(*cp, *ema);
+                let expected = match i {
+                    0..=2 => ComputeStatus::Warming,
+                    3 => ComputeStatus::JustReady,
+                    _ => ComputeStatus::Updated,
+                };
+                assert_eq!(status, expected);
+                assert_eq!(
+                    status == ComputeStatus::JustReady,
+                    !was_ready && ma.latest().is_some()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nth_back() {
+        let mut ma = MAData::new(2);
+        for cp in [1.0, 3.0, 5.0, 7.0, 9.0, 11.0].iter() {
+            ma.compute(*cp, false);
+        }
+
+        assert_eq!(ma.nth_back(0), Some(10.0));
+        assert_eq!(ma.nth_back(1), ma.penultimate());
+        assert_eq!(ma.nth_back(3), Some(4.0));
+        assert_eq!(ma.nth_back(4), None);
+
+        let mut ma = MAData::with_history(2, 6);
+        for cp in [1.0, 3.0, 5.0, 7.0, 9.0, 11.0].iter() {
+            ma.compute(*cp, false);
+        }
+        assert_eq!(ma.nth_back(4), Some(2.0));
+        assert_eq!(ma.nth_back(5), None);
+    }
+}
@@ -1,18 +1,60 @@
 mod account;
 mod account_manager;
+mod adx;
+mod alligator;
+mod backtest;
 mod balance;
 mod binance;
+mod bollinger;
+mod bop;
+mod calibration;
 mod candlestick;
 mod config;
+mod connors;
+mod correlation;
+mod cycle;
+mod demarker;
+mod disparity;
+mod dpo;
+mod error;
 mod exchangeinfo;
+mod fib;
+mod hysteresis;
+mod impulse;
+mod kama;
+mod keltner;
+mod kst;
+mod linreg;
 mod ma;
+mod massindex;
+mod mcginley;
 mod order;
 mod orderbook;
+mod percentile;
+mod portfolio;
 mod position;
+mod prevclose;
 mod price;
 mod process_md;
+mod pvt;
+mod regime;
+mod relative_strength;
+mod roc;
+mod rsi;
+mod smoother;
+mod specialk;
+mod squeeze;
+mod stc;
+mod streak;
 mod tradingpair;
+mod trendline;
+mod twiggs;
+mod ultimate;
 mod utils;
+mod volume;
+mod vwap;
+mod window;
+mod zscore;
 
 use log::debug;
 
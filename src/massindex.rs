@@ -0,0 +1,140 @@
+// Mass Index (massindex.rs), sums the ratio of a single and double EMA of the
+// high-low range, widening ranges push it up ahead of trend reversals.
+// https://www.investopedia.com/terms/m/mass-index.asp
+use crate::ma;
+use crate::window;
+
+use ma::MAData;
+use window::RollingWindow;
+
+// Period of both EMAs of the range, and number of ratios summed.
+static MASS_INDEX_EMA_PERIOD: u16 = 9;
+static MASS_INDEX_SUM_PERIOD: usize = 25;
+
+// A reversal bulge is the index rising above the upper level then falling back
+// below the lower.
+static MASS_INDEX_BULGE_UPPER: f64 = 27.0;
+static MASS_INDEX_BULGE_LOWER: f64 = 26.5;
+
+#[derive(Debug, Clone)]
+pub struct MassIndex {
+    // EMA of the range, and EMA of that EMA.
+    range_ema: MAData,
+    double_ema: MAData,
+
+    ratios: RollingWindow<f64>,
+
+    // Has the index been above the upper bulge level since it was last below the
+    // lower.
+    bulge: bool,
+    reversal: bool,
+
+    latest: Option<f64>,
+}
+
+impl MassIndex {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        MassIndex {
+            range_ema: MAData::new(MASS_INDEX_EMA_PERIOD),
+            double_ema: MAData::new(MASS_INDEX_EMA_PERIOD),
+            ratios: RollingWindow::new(MASS_INDEX_SUM_PERIOD),
+            bulge: false,
+            reversal: false,
+            latest: None,
+        }
+    }
+
+    // Current Mass Index value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Whether the latest value completed a reversal bulge.
+    #[allow(dead_code)]
+    pub fn reversal(&self) -> bool {
+        self.reversal
+    }
+
+    // Compute the latest value from the candle's high and low.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, high: f64, low: f64) {
+        if !high.is_finite() || !low.is_finite() {
+            return;
+        }
+
+        self.range_ema.compute(high - low, true);
+        if let Some(range_ema) = self.range_ema.latest() {
+            self.double_ema.compute(range_ema, true);
+        }
+
+        if let (Some(range_ema), Some(double_ema)) =
+            (self.range_ema.latest(), self.double_ema.latest())
+        {
+            let ratio = if double_ema > 0.0 {
+                range_ema / double_ema
+            } else {
+                1.0
+            };
+            self.ratios.push(ratio);
+        }
+
+        self.reversal = false;
+        if self.ratios.is_full() {
+            let index = self.ratios.sum();
+            if index > MASS_INDEX_BULGE_UPPER {
+                self.bulge = true;
+            } else if self.bulge && index < MASS_INDEX_BULGE_LOWER {
+                self.bulge = false;
+                self.reversal = true;
+            }
+            self.latest = Some(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expanding_range() {
+        let mut mi = MassIndex::new();
+        for _ in 0..60 {
+            mi.compute(101.0, 99.0);
+        }
+        let steady = mi.latest().unwrap();
+        assert!((steady - 25.0).abs() < 1e-9);
+
+        let mut prev = steady;
+        for i in 1..6 {
+            let half_range = 1.0 + i as f64;
+            mi.compute(100.0 + half_range, 100.0 - half_range);
+            let latest = mi.latest().unwrap();
+            assert!(latest > prev);
+            prev = latest;
+        }
+    }
+
+    #[test]
+    fn reversal_bulge() {
+        let mut mi = MassIndex::new();
+        for _ in 0..60 {
+            mi.compute(101.0, 99.0);
+        }
+
+        let mut reversals = 0;
+        let mut peak: f64 = 0.0;
+        for i in 0..80 {
+            let half_range = if i < 10 { 1.0 + i as f64 * 2.0 } else { 1.0 };
+            mi.compute(100.0 + half_range, 100.0 - half_range);
+            peak = peak.max(mi.latest().unwrap());
+            if mi.reversal() {
+                reversals += 1;
+            }
+        }
+        assert!(peak > 27.0);
+        assert_eq!(reversals, 1);
+    }
+}
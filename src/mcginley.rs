@@ -0,0 +1,90 @@
+// McGinley dynamic (mcginley.rs), a moving average which adjusts its speed to the
+// market, speeding up as the price falls away from it and reducing whipsaws.
+// https://www.investopedia.com/terms/m/mcginley-dynamic.asp
+
+// Constant scaling the period, 60% of N as suggested by McGinley.
+static MCGINLEY_K: f64 = 0.6;
+
+#[derive(Debug)]
+pub struct McGinley {
+    pub period: u16,
+    latest: Option<f64>,
+}
+
+impl McGinley {
+    #[allow(dead_code)]
+    pub fn new(period: u16) -> Self {
+        McGinley {
+            period,
+            latest: None,
+        }
+    }
+
+    // Current McGinley dynamic value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Compute the latest value based on the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if self.period == 0 || !close_price.is_finite() {
+            return;
+        }
+
+        self.latest = match self.latest {
+            Some(md) if md != 0.0 => {
+                let ratio = close_price / md;
+                let denominator = MCGINLEY_K * self.period as f64 * ratio.powi(4);
+                Some(md + (close_price - md) / denominator)
+            }
+            // Seed with the first close.
+            _ => Some(close_price),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ma::MAData;
+
+    #[test]
+    fn trend() {
+        let mut md = McGinley::new(10);
+        md.compute(100.0);
+        assert_eq!(md.latest(), Some(100.0));
+
+        let mut prev = 100.0;
+        for i in 1..50 {
+            let cp = 100.0 + i as f64;
+            md.compute(cp);
+            let latest = md.latest().unwrap();
+            assert!(latest > prev && latest < cp);
+            prev = latest;
+        }
+    }
+
+    #[test]
+    fn accelerating_drop() {
+        let mut md = McGinley::new(10);
+        let mut ema = MAData::new(10);
+        for _ in 0..20 {
+            md.compute(100.0);
+            ema.compute(100.0, true);
+        }
+
+        // Falling faster and faster, the dynamic speeds up to follow.
+        let mut cp = 100.0;
+        for i in 0..15 {
+            cp -= i as f64 * 0.5;
+            md.compute(cp);
+            ema.compute(cp, true);
+        }
+
+        let md_lag = md.latest().unwrap() - cp;
+        let ema_lag = ema.latest().unwrap() - cp;
+        assert!(md_lag > 0.0 && md_lag < ema_lag);
+    }
+}
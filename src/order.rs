@@ -1,9 +1,13 @@
+use crate::account_manager::OrderQuantity;
 use crate::binance::Binance;
 use crate::position;
+use crate::process_md::MarketDataTracker;
 use crate::tradingpair::TradingPair;
 
 use position::PositionType;
 
+use math::round;
+
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
@@ -20,12 +24,82 @@ pub enum OrderType {
     Limit,
 }
 
+// What a signal against the current position does. DirectFlip reverses it in one
+// order, FlatFirst closes it then opens the new position in a second order.
+#[derive(Debug, Clone, PartialEq, Copy, Default)]
+pub enum FlipPolicy {
+    #[default]
+    DirectFlip,
+    #[allow(dead_code)]
+    FlatFirst,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum OrderError {
     #[allow(dead_code)]
     MinNotional,
 }
 
+// An actionable order derived from a trading signal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderIntent {
+    pub side: PositionType,
+    pub target_qty: f64,
+    pub limit_price: Option<f64>,
+    pub reason: String,
+}
+
+// How much of the available funds to commit to an order.
+#[derive(Debug, Clone)]
+pub struct Sizing {
+    pub quantity: OrderQuantity,
+    // Quote currency available for buys, base currency held for sells.
+    pub available: f64,
+}
+
+// Leveraged tokens (BVLTs) traded in place of an underlying, a long signal on the
+// underlying buys the UP token and a short signal buys the DOWN token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BvltConfig {
+    // Target leverage of the tokens, e.g. 3.0.
+    pub leverage: f64,
+    pub up_symbol: String,
+    pub down_symbol: String,
+    // When set, buy at most enough of the token for this much exposure to the
+    // underlying, in the quote currency.
+    pub target_exposure: Option<f64>,
+    // Percentage taken off the token order to allow for the tokens' decay and
+    // funding costs.
+    pub decay_haircut_pct: Option<f64>,
+}
+
+impl BvltConfig {
+    // Token to buy for a signal on the underlying.
+    pub fn token_for(&self, signal: PositionType) -> Option<&str> {
+        match signal {
+            PositionType::Long => Some(&self.up_symbol),
+            PositionType::Short => Some(&self.down_symbol),
+            PositionType::None => None,
+        }
+    }
+
+    // Exposure to the underlying from holding 'notional' worth of either token.
+    #[allow(dead_code)]
+    pub fn underlying_exposure(&self, notional: f64) -> f64 {
+        notional * self.leverage
+    }
+
+    // Worth of the token to buy out of 'notional', capped at the target exposure
+    // and less the decay haircut.
+    pub fn token_notional(&self, notional: f64) -> f64 {
+        let notional = match self.target_exposure {
+            Some(target) if self.leverage > 0.0 => notional.min(target / self.leverage),
+            _ => notional,
+        };
+        notional * (1.0 - self.decay_haircut_pct.unwrap_or(0.0) / 100.0)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[allow(non_snake_case)]
 pub struct Fill {
@@ -128,6 +202,176 @@ impl Fill {
     }
 }
 
+// Compute the limit price we are willing to accept, 'limit_offset' ticks away from
+// the close price in the direction of the trade.
+pub fn limit_price(
+    tp: &TradingPair,
+    position: PositionType,
+    closing_price: f64,
+    limit_offset: u8,
+) -> f64 {
+    let offset = tp.get_tick_size() * limit_offset as f64;
+    if position == PositionType::Long {
+        round::floor(closing_price + offset, tp.get_price_dps())
+    } else {
+        round::floor(closing_price - offset, tp.get_price_dps())
+    }
+}
+
+// Turn a trading signal into an order intent based on the current price and sizing
+// rules. Returns None if there is nothing to do or the order would be too small.
+#[allow(dead_code)]
+pub fn to_intent(
+    signal: PositionType,
+    mt: &MarketDataTracker,
+    tp: &TradingPair,
+    closing_price: f64,
+    sizing: &Sizing,
+) -> Option<OrderIntent> {
+    if signal == PositionType::None {
+        return None;
+    }
+
+    let limit_price = intent_limit_price(signal, mt, tp, closing_price);
+    let price = limit_price.unwrap_or(closing_price);
+    let target_qty = round::floor(
+        if signal == PositionType::Long {
+            sizing.quantity.of(sizing.available / price)
+        } else {
+            // Always sell all.
+            sizing.available
+        },
+        tp.get_qty_dps(),
+    );
+
+    if target_qty <= 0.0 || target_qty * price < tp.get_min_notional() {
+        return None;
+    }
+
+    Some(OrderIntent {
+        side: signal,
+        target_qty,
+        limit_price,
+        reason: format!("{:?} signal on {}", mt.trade_signal, tp.symbol()),
+    })
+}
+
+// Limit price for an order in the signal's direction, None for market orders.
+fn intent_limit_price(
+    signal: PositionType,
+    mt: &MarketDataTracker,
+    tp: &TradingPair,
+    closing_price: f64,
+) -> Option<f64> {
+    if mt.order_type == OrderType::Limit {
+        Some(limit_price(
+            tp,
+            signal,
+            closing_price,
+            mt.limit_offset
+                .expect("limit offset is None but this is a limit order"),
+        ))
+    } else {
+        None
+    }
+}
+
+// As to_intent() but aware of the current position. A signal against a position of
+// 'held_qty' closes it as the tracker's flip policy says, then opens the new
+// position as sized by to_intent(). A signal in the direction already held, or
+// with no position, gives to_intent()'s order alone.
+#[allow(dead_code)]
+pub fn to_intents(
+    signal: PositionType,
+    current: PositionType,
+    held_qty: f64,
+    mt: &MarketDataTracker,
+    tp: &TradingPair,
+    closing_price: f64,
+    sizing: &Sizing,
+) -> Vec<OrderIntent> {
+    let open = to_intent(signal, mt, tp, closing_price, sizing);
+    let held_qty = round::floor(held_qty, tp.get_qty_dps());
+    if signal == PositionType::None
+        || current == PositionType::None
+        || current == signal
+        || held_qty <= 0.0
+    {
+        return open.into_iter().collect();
+    }
+
+    let limit_price = intent_limit_price(signal, mt, tp, closing_price);
+    match mt.flip_policy {
+        FlipPolicy::DirectFlip => {
+            let open_qty = open.map_or(0.0, |open| open.target_qty);
+            vec![OrderIntent {
+                side: signal,
+                target_qty: round::floor(held_qty + open_qty, tp.get_qty_dps()),
+                limit_price,
+                reason: format!(
+                    "{:?} signal on {}, reversing {}",
+                    mt.trade_signal,
+                    tp.symbol(),
+                    current
+                ),
+            }]
+        }
+        FlipPolicy::FlatFirst => {
+            let close = OrderIntent {
+                side: signal,
+                target_qty: held_qty,
+                limit_price,
+                reason: format!(
+                    "{:?} signal on {}, closing {}",
+                    mt.trade_signal,
+                    tp.symbol(),
+                    current
+                ),
+            };
+            std::iter::once(close).chain(open).collect()
+        }
+    }
+}
+
+// Turn a trading signal on the underlying into an order intent buying the matching
+// BVLT, sized from the token's price rather than the underlying's. 'token' is the
+// trading pair of the token to buy, returns None if it doesn't match the signal.
+// The order is reduced to the config's target exposure and decay haircut, see
+// BvltConfig::token_notional(). Selling any opposite token held is left to the
+// caller.
+#[allow(dead_code)]
+pub fn to_bvlt_intent(
+    signal: PositionType,
+    mt: &MarketDataTracker,
+    bvlt: &BvltConfig,
+    token: &TradingPair,
+    token_price: f64,
+    sizing: &Sizing,
+) -> Option<OrderIntent> {
+    if bvlt.token_for(signal)? != token.symbol() {
+        return None;
+    }
+
+    let mut intent = to_intent(PositionType::Long, mt, token, token_price, sizing)?;
+    let price = intent.limit_price.unwrap_or(token_price);
+    intent.target_qty = round::floor(
+        bvlt.token_notional(intent.target_qty * price) / price,
+        token.get_qty_dps(),
+    );
+    if intent.target_qty <= 0.0 || intent.target_qty * price < token.get_min_notional() {
+        return None;
+    }
+
+    intent.reason = format!(
+        "{:?} {:?} signal, buying {} at {}x",
+        mt.trade_signal,
+        signal,
+        token.symbol(),
+        bvlt.leverage
+    );
+    Some(intent)
+}
+
 fn place_limit_order_internal(
     bex: &Binance,
     tp: &TradingPair,
@@ -232,3 +476,141 @@ pub fn place_stop_limit(
 
     ex.send_stop_order(&order_params)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::process_md::TradeSignal;
+
+    #[test]
+    fn buy_intent() {
+        let tp = TradingPair::with_filters("ADA/USDT", 4, 1, 0.1, 0.0001, 10.0);
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        let sizing = Sizing {
+            quantity: OrderQuantity::Percentage50,
+            available: 1000.0,
+        };
+
+        let intent = to_intent(PositionType::Long, &mt, &tp, 2.0, &sizing).unwrap();
+        assert_eq!(intent.side, PositionType::Long);
+        assert_eq!(intent.target_qty, 250.0);
+        assert_eq!(intent.limit_price, None);
+
+        mt.order_type = OrderType::Limit;
+        mt.limit_offset = Some(2);
+        let intent = to_intent(PositionType::Long, &mt, &tp, 2.0, &sizing).unwrap();
+        assert_eq!(intent.limit_price, Some(2.0002));
+        assert_eq!(intent.target_qty, 249.9);
+
+        assert!(to_intent(PositionType::None, &mt, &tp, 2.0, &sizing).is_none());
+    }
+
+    #[test]
+    fn flip_policy() {
+        let tp = TradingPair::with_filters("ADA/USDT", 4, 1, 0.1, 0.0001, 10.0);
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        let sizing = Sizing {
+            quantity: OrderQuantity::Percentage100,
+            available: 300.0,
+        };
+
+        assert_eq!(mt.flip_policy, FlipPolicy::DirectFlip);
+        let intents = to_intents(
+            PositionType::Short,
+            PositionType::Long,
+            500.0,
+            &mt,
+            &tp,
+            2.0,
+            &sizing,
+        );
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].side, PositionType::Short);
+        assert_eq!(intents[0].target_qty, 800.0);
+        assert!(intents[0].reason.contains("reversing LONG"));
+
+        mt.flip_policy = FlipPolicy::FlatFirst;
+        let intents = to_intents(
+            PositionType::Short,
+            PositionType::Long,
+            500.0,
+            &mt,
+            &tp,
+            2.0,
+            &sizing,
+        );
+        let qtys: Vec<f64> = intents.iter().map(|intent| intent.target_qty).collect();
+        assert_eq!(qtys, vec![500.0, 300.0]);
+        assert!(intents
+            .iter()
+            .all(|intent| intent.side == PositionType::Short));
+        assert!(intents[0].reason.contains("closing LONG"));
+
+        // Nothing to reverse, just the opening order.
+        let intents = to_intents(
+            PositionType::Short,
+            PositionType::None,
+            0.0,
+            &mt,
+            &tp,
+            2.0,
+            &sizing,
+        );
+        assert_eq!(
+            intents,
+            vec![to_intent(PositionType::Short, &mt, &tp, 2.0, &sizing).unwrap()]
+        );
+    }
+
+    #[test]
+    fn bvlt_intent() {
+        let bvlt = BvltConfig {
+            leverage: 3.0,
+            up_symbol: String::from("BTCUPUSDT"),
+            down_symbol: String::from("BTCDOWNUSDT"),
+            target_exposure: None,
+            decay_haircut_pct: None,
+        };
+        let up = TradingPair::with_filters("BTCUP/USDT", 3, 2, 0.01, 0.001, 10.0);
+        let down = TradingPair::with_filters("BTCDOWN/USDT", 3, 2, 0.01, 0.001, 10.0);
+        let mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        let sizing = Sizing {
+            quantity: OrderQuantity::Percentage100,
+            available: 1000.0,
+        };
+
+        // A long on the underlying buys the UP token, sized at the token's price.
+        let intent = to_bvlt_intent(PositionType::Long, &mt, &bvlt, &up, 40.0, &sizing).unwrap();
+        assert_eq!(intent.side, PositionType::Long);
+        assert_eq!(intent.target_qty, 25.0);
+        assert!(intent.reason.contains("BTCUPUSDT at 3x"));
+        assert_eq!(bvlt.underlying_exposure(intent.target_qty * 40.0), 3000.0);
+        assert!(to_bvlt_intent(PositionType::Long, &mt, &bvlt, &down, 40.0, &sizing).is_none());
+
+        // And a short buys the DOWN token.
+        let intent = to_bvlt_intent(PositionType::Short, &mt, &bvlt, &down, 8.0, &sizing).unwrap();
+        assert_eq!(intent.side, PositionType::Long);
+        assert_eq!(intent.target_qty, 125.0);
+        assert!(to_bvlt_intent(PositionType::None, &mt, &bvlt, &up, 40.0, &sizing).is_none());
+
+        // For the same exposure to the underlying, 3x tokens need a third of the
+        // 1x quantity.
+        let qty = |leverage: f64, decay_haircut_pct: Option<f64>| {
+            let bvlt = BvltConfig {
+                leverage,
+                target_exposure: Some(600.0),
+                decay_haircut_pct,
+                ..bvlt.clone()
+            };
+            to_bvlt_intent(PositionType::Long, &mt, &bvlt, &up, 40.0, &sizing)
+                .unwrap()
+                .target_qty
+        };
+        assert_eq!(qty(1.0, None), 15.0);
+        assert_eq!(qty(3.0, None), 5.0);
+        assert_eq!(qty(3.0, Some(10.0)), 4.5);
+        // Never more than the sizing allows.
+        assert_eq!(qty(0.5, None), 25.0);
+    }
+}
@@ -0,0 +1,94 @@
+// Percent rank (percentile.rs), where a value sits within its own recent history
+// so any indicator can be read as a percentile, e.g. is this RSI extreme for the
+// pair.
+use std::collections::VecDeque;
+
+// Percentage, 0..100, of the historical values below 'current'. An empty history
+// ranks 0.
+pub fn percent_rank(current: f64, history: &VecDeque<f64>) -> f64 {
+    if history.is_empty() {
+        return 0.0;
+    }
+
+    let below = history.iter().filter(|v| **v < current).count();
+    100.0 * below as f64 / history.len() as f64
+}
+
+// Keeps a bounded history of an indicator's values to rank each new one against.
+#[derive(Debug, Clone)]
+pub struct PercentRank {
+    // Values newest at the back.
+    history: VecDeque<f64>,
+    depth: usize,
+
+    latest: Option<f64>,
+}
+
+impl PercentRank {
+    #[allow(dead_code)]
+    pub fn new(depth: usize) -> Self {
+        PercentRank {
+            history: VecDeque::with_capacity(depth),
+            depth,
+            latest: None,
+        }
+    }
+
+    // Percent rank of the last value computed against the history before it.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Rank 'value' against the history, then add it to the history.
+    pub fn compute(&mut self, value: f64) {
+        if !value.is_finite() || self.depth == 0 {
+            return;
+        }
+
+        if !self.history.is_empty() {
+            self.latest = Some(percent_rank(value, &self.history));
+        }
+
+        if self.history.len() == self.depth {
+            self.history.pop_front();
+        }
+        self.history.push_back(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank() {
+        let history: VecDeque<f64> = (1..=9).map(|v| v as f64).collect();
+        assert_eq!(percent_rank(10.0, &history), 100.0);
+        assert_eq!(percent_rank(0.0, &history), 0.0);
+
+        let median = percent_rank(5.5, &history);
+        assert!((median - 50.0).abs() < 10.0);
+
+        let empty = VecDeque::new();
+        assert_eq!(percent_rank(1.0, &empty), 0.0);
+    }
+
+    #[test]
+    fn bounded_history() {
+        let mut pr = PercentRank::new(4);
+        pr.compute(1.0);
+        assert!(pr.latest().is_none());
+
+        for v in [2.0, 3.0, 4.0, 5.0].iter() {
+            pr.compute(*v);
+            assert_eq!(pr.latest(), Some(100.0));
+        }
+
+        // 1.0 has left the history, so 2.0 is now the lowest.
+        pr.compute(2.0);
+        assert_eq!(pr.latest(), Some(0.0));
+        pr.compute(4.5);
+        assert_eq!(pr.latest(), Some(75.0));
+    }
+}
@@ -0,0 +1,106 @@
+// Portfolio (portfolio.rs), market data trackers for many trading pairs keyed by
+// symbol.
+use crate::candlestick;
+use crate::position;
+use crate::process_md;
+use crate::tradingpair;
+
+use candlestick::Candle;
+use position::PositionType;
+use process_md::MarketDataTracker;
+use std::collections::HashMap;
+use tradingpair::TradingPair;
+
+#[derive(Debug, Default)]
+pub struct Portfolio {
+    pub trackers: HashMap<String, MarketDataTracker>,
+    pairs: HashMap<String, TradingPair>,
+}
+
+impl Portfolio {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Portfolio::default()
+    }
+
+    // Track a trading pair, replacing any tracker already held for its symbol.
+    #[allow(dead_code)]
+    pub fn add(&mut self, tp: TradingPair, mut mt: MarketDataTracker) {
+        let symbol = tp.symbol().to_string();
+        mt.symbol = Some(symbol.clone());
+        self.trackers.insert(symbol.clone(), mt);
+        self.pairs.insert(symbol, tp);
+    }
+
+    // Update the symbol's tracker with a closed candle and check its signal.
+    // None if the symbol isn't tracked.
+    #[allow(dead_code)]
+    pub fn on_candle(&mut self, symbol: &str, candle: &Candle) -> Option<PositionType> {
+        let mt = self.trackers.get_mut(symbol)?;
+        let tp = self.pairs.get(symbol)?;
+
+        mt.on_candle(candle);
+        Some(mt.signal(tp, candle.close))
+    }
+
+    // Current desired position of every tracked symbol, ordered by symbol.
+    #[allow(dead_code)]
+    pub fn signals(&self) -> Vec<(String, PositionType)> {
+        let mut signals: Vec<(String, PositionType)> = self
+            .trackers
+            .iter()
+            .map(|(symbol, mt)| (symbol.clone(), mt.desired_position))
+            .collect();
+        signals.sort_by(|a, b| a.0.cmp(&b.0));
+
+        signals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use process_md::TradeSignal;
+
+    fn candle(close: f64) -> Candle {
+        Candle {
+            open_time: 0,
+            close_time: 0,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+            symbol: None,
+        }
+    }
+
+    #[test]
+    fn independent_signals() {
+        let mut portfolio = Portfolio::new();
+        for symbol in ["BTC/USDT", "ETH/USDT"].iter() {
+            portfolio.add(
+                TradingPair::with_filters(symbol, 2, 5, 0.00001, 0.01, 10.0),
+                MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false),
+            );
+        }
+
+        // BTC crosses up, ETH crosses down.
+        let btc = [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0];
+        let eth = [8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 8.0];
+        for (b, e) in btc.iter().zip(eth.iter()) {
+            portfolio.on_candle("BTCUSDT", &candle(*b));
+            portfolio.on_candle("ETHUSDT", &candle(*e));
+        }
+
+        assert_eq!(portfolio.on_candle("XRPUSDT", &candle(1.0)), None);
+        assert_eq!(
+            portfolio.signals(),
+            vec![
+                ("BTCUSDT".to_string(), PositionType::Long),
+                ("ETHUSDT".to_string(), PositionType::Short),
+            ]
+        );
+        assert_eq!(portfolio.trackers["BTCUSDT"].candle_count, 7);
+    }
+}
@@ -1,3 +1,9 @@
+use crate::error;
+
+use error::CtError;
+use std::fmt;
+use std::str::FromStr;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum PositionType {
     Long,
@@ -5,9 +11,403 @@ pub enum PositionType {
     None,
 }
 
+impl PositionType {
+    // The other direction, None stays None.
+    pub fn inverse(self) -> Self {
+        match self {
+            PositionType::Long => PositionType::Short,
+            PositionType::Short => PositionType::Long,
+            PositionType::None => PositionType::None,
+        }
+    }
+}
+
+impl fmt::Display for PositionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionType::Long => write!(f, "LONG"),
+            PositionType::Short => write!(f, "SHORT"),
+            PositionType::None => write!(f, "NONE"),
+        }
+    }
+}
+
+impl FromStr for PositionType {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LONG" => Ok(PositionType::Long),
+            "SHORT" => Ok(PositionType::Short),
+            "NONE" => Ok(PositionType::None),
+            _ => Err(CtError::Parse {
+                input: s.to_string(),
+            }),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Position {
     pub r#type: PositionType,
     pub qty: f64,
     pub price: f64,
 }
+
+// Portion of a position to close, as a fraction of its original quantity.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PartialExit {
+    pub fraction: f64,
+    pub price: f64,
+}
+
+// Take profit which closes portions of a position at successive profit levels,
+// then trails the remainder by 'trail_percent' from the best price seen.
+#[derive(Debug, Clone)]
+pub struct StagedTakeProfit {
+    r#type: PositionType,
+    entry_price: f64,
+    // (profit percent, fraction of the original quantity to close), in order.
+    tiers: Vec<(f64, f64)>,
+    next_tier: usize,
+    trail_percent: f64,
+    remaining: f64,
+    best_price: f64,
+}
+
+impl StagedTakeProfit {
+    #[allow(dead_code)]
+    pub fn new(position: &Position, tiers: Vec<(f64, f64)>, trail_percent: f64) -> Self {
+        StagedTakeProfit {
+            r#type: position.r#type,
+            entry_price: position.price,
+            tiers,
+            next_tier: 0,
+            trail_percent,
+            remaining: 1.0,
+            best_price: position.price,
+        }
+    }
+
+    // Fraction of the original quantity still open.
+    #[allow(dead_code)]
+    pub fn remaining(&self) -> f64 {
+        self.remaining
+    }
+
+    // Check the latest price against the profit levels, returning the portion of
+    // the position to close if any were reached.
+    #[allow(dead_code)]
+    pub fn update(&mut self, price: f64) -> Option<PartialExit> {
+        if self.remaining <= 0.0 || self.r#type == PositionType::None {
+            return None;
+        }
+
+        let profit_percent = match self.r#type {
+            PositionType::Short => (self.entry_price - price) * 100.0 / self.entry_price,
+            _ => (price - self.entry_price) * 100.0 / self.entry_price,
+        };
+
+        let mut fraction = 0.0;
+        while self.next_tier < self.tiers.len() {
+            let (tier_percent, tier_fraction) = self.tiers[self.next_tier];
+            if profit_percent < tier_percent {
+                break;
+            }
+
+            fraction += tier_fraction.min(self.remaining - fraction);
+            self.next_tier += 1;
+            self.best_price = price;
+        }
+
+        if self.next_tier == self.tiers.len() && fraction == 0.0 {
+            // All tiers taken, trail the rest.
+            let retrace_percent = match self.r#type {
+                PositionType::Short => {
+                    self.best_price = self.best_price.min(price);
+                    (price - self.best_price) * 100.0 / self.best_price
+                }
+                _ => {
+                    self.best_price = self.best_price.max(price);
+                    (self.best_price - price) * 100.0 / self.best_price
+                }
+            };
+
+            if retrace_percent >= self.trail_percent {
+                fraction = self.remaining;
+            }
+        }
+
+        if fraction > 0.0 {
+            self.remaining -= fraction;
+            Some(PartialExit { fraction, price })
+        } else {
+            None
+        }
+    }
+}
+
+// Maximum favourable (MFE) and adverse (MAE) excursion of an open position, as
+// percentages of the entry price in the position's favour and against it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExcursionTracker {
+    r#type: PositionType,
+    entry_price: f64,
+    mfe: f64,
+    mae: f64,
+}
+
+impl ExcursionTracker {
+    #[allow(dead_code)]
+    pub fn new(position: &Position) -> Self {
+        ExcursionTracker {
+            r#type: position.r#type,
+            entry_price: position.price,
+            mfe: 0.0,
+            mae: 0.0,
+        }
+    }
+
+    // Largest profit seen, in percent.
+    #[allow(dead_code)]
+    pub fn mfe(&self) -> f64 {
+        self.mfe
+    }
+
+    // Largest loss seen, in percent.
+    #[allow(dead_code)]
+    pub fn mae(&self) -> f64 {
+        self.mae
+    }
+
+    // Update with the latest price.
+    #[allow(dead_code)]
+    pub fn update(&mut self, price: f64) {
+        if !price.is_finite() || self.entry_price == 0.0 {
+            return;
+        }
+
+        let profit_percent = match self.r#type {
+            PositionType::Long => (price - self.entry_price) * 100.0 / self.entry_price,
+            PositionType::Short => (self.entry_price - price) * 100.0 / self.entry_price,
+            PositionType::None => return,
+        };
+
+        self.mfe = self.mfe.max(profit_percent);
+        self.mae = self.mae.max(-profit_percent);
+    }
+}
+
+// Blocks new entries after 'max_losses' losing trades in a row, until a win.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LossStreakGuard {
+    pub max_losses: u32,
+    losses: u32,
+}
+
+impl LossStreakGuard {
+    #[allow(dead_code)]
+    pub fn new(max_losses: u32) -> Self {
+        LossStreakGuard {
+            max_losses,
+            losses: 0,
+        }
+    }
+
+    // Record the realised profit or loss of a closed trade.
+    pub fn record_outcome(&mut self, pnl: f64) {
+        if pnl < 0.0 {
+            self.losses += 1;
+        } else if pnl > 0.0 {
+            self.losses = 0;
+        }
+    }
+
+    // Number of losing trades in a row.
+    #[allow(dead_code)]
+    pub fn losses(&self) -> u32 {
+        self.losses
+    }
+
+    pub fn entries_allowed(&self) -> bool {
+        self.max_losses == 0 || self.losses < self.max_losses
+    }
+}
+
+// Trips once equity falls more than 'max_drawdown_pct' below its peak, and stays
+// tripped until reset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreaker {
+    pub max_drawdown_pct: f64,
+    peak_equity: Option<f64>,
+    tripped: bool,
+}
+
+impl CircuitBreaker {
+    #[allow(dead_code)]
+    pub fn new(max_drawdown_pct: f64) -> Self {
+        CircuitBreaker {
+            max_drawdown_pct,
+            peak_equity: None,
+            tripped: false,
+        }
+    }
+
+    // Record the current account value, returns true if this trips the breaker.
+    pub fn record_equity(&mut self, value: f64) -> bool {
+        if !value.is_finite() || self.tripped {
+            return false;
+        }
+
+        let peak = self.peak_equity.unwrap_or(value).max(value);
+        self.peak_equity = Some(peak);
+
+        if peak > 0.0 && 100.0 * (peak - value) / peak > self.max_drawdown_pct {
+            self.tripped = true;
+        }
+        self.tripped
+    }
+
+    // Current drawdown from the peak equity, in percent.
+    #[allow(dead_code)]
+    pub fn drawdown_pct(&self, value: f64) -> Option<f64> {
+        self.peak_equity
+            .filter(|peak| *peak > 0.0)
+            .map(|peak| 100.0 * (peak - value) / peak)
+    }
+
+    pub fn tripped(&self) -> bool {
+        self.tripped
+    }
+
+    // Resume trading, measuring drawdown from the next equity recorded.
+    #[allow(dead_code)]
+    pub fn reset(&mut self) {
+        self.peak_equity = None;
+        self.tripped = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exit_fraction(exit: Option<PartialExit>) -> f64 {
+        exit.map(|e| e.fraction).unwrap_or(0.0)
+    }
+
+    #[test]
+    fn staged_take_profit() {
+        let position = Position {
+            r#type: PositionType::Long,
+            qty: 3.0,
+            price: 100.0,
+        };
+        let mut tp =
+            StagedTakeProfit::new(&position, vec![(2.0, 1.0 / 3.0), (4.0, 1.0 / 3.0)], 1.0);
+
+        assert_eq!(tp.update(101.0), None);
+        assert!((exit_fraction(tp.update(102.0)) - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(tp.update(103.0), None);
+        assert!((exit_fraction(tp.update(104.5)) - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(tp.update(106.0), None);
+        assert_eq!(tp.update(105.0), None);
+        assert!((exit_fraction(tp.update(104.9)) - 1.0 / 3.0).abs() < 1e-9);
+        assert!(tp.remaining().abs() < 1e-9);
+        assert_eq!(tp.update(110.0), None);
+    }
+
+    #[test]
+    fn staged_take_profit_short() {
+        let position = Position {
+            r#type: PositionType::Short,
+            qty: 2.0,
+            price: 100.0,
+        };
+        let mut tp = StagedTakeProfit::new(&position, vec![(2.0, 0.5), (4.0, 0.25)], 1.0);
+
+        // Both tiers reached on one candle.
+        assert!((exit_fraction(tp.update(95.0)) - 0.75).abs() < 1e-9);
+        assert_eq!(tp.update(94.0), None);
+        assert!((exit_fraction(tp.update(95.0)) - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn position_type_display() {
+        for (position, s) in [
+            (PositionType::Long, "LONG"),
+            (PositionType::Short, "SHORT"),
+            (PositionType::None, "NONE"),
+        ]
+        .iter()
+        {
+            assert_eq!(position.to_string(), *s);
+            assert_eq!(s.parse::<PositionType>(), Ok(*position));
+        }
+
+        assert!("long".parse::<PositionType>().is_err());
+    }
+
+    #[test]
+    fn excursion_tracker() {
+        let long = Position {
+            r#type: PositionType::Long,
+            qty: 1.0,
+            price: 100.0,
+        };
+        let mut et = ExcursionTracker::new(&long);
+        for price in [99.0, 98.0, 103.0, 110.0, 104.0, 101.0].iter() {
+            et.update(*price);
+        }
+        assert!((et.mfe() - 10.0).abs() < 1e-9);
+        assert!((et.mae() - 2.0).abs() < 1e-9);
+
+        let short = Position {
+            r#type: PositionType::Short,
+            ..long
+        };
+        let mut et = ExcursionTracker::new(&short);
+        for price in [101.0, 95.0, 100.0].iter() {
+            et.update(*price);
+        }
+        assert!((et.mfe() - 5.0).abs() < 1e-9);
+        assert!((et.mae() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn circuit_breaker() {
+        let mut breaker = CircuitBreaker::new(10.0);
+        assert!(!breaker.record_equity(1000.0));
+        assert!(!breaker.record_equity(1200.0));
+        // Exactly 10% off the peak is still allowed.
+        assert!(!breaker.record_equity(1080.0));
+        assert_eq!(breaker.drawdown_pct(1080.0), Some(10.0));
+
+        assert!(breaker.record_equity(1070.0));
+        assert!(breaker.tripped());
+        // Stays tripped as equity recovers.
+        assert!(!breaker.record_equity(1300.0));
+        assert!(breaker.tripped());
+
+        breaker.reset();
+        assert!(!breaker.tripped());
+        assert!(!breaker.record_equity(1000.0));
+    }
+
+    #[test]
+    fn loss_streak_guard() {
+        let mut guard = LossStreakGuard::new(3);
+        guard.record_outcome(-1.0);
+        guard.record_outcome(-2.0);
+        // Break even doesn't end the streak.
+        guard.record_outcome(0.0);
+        assert!(guard.entries_allowed());
+
+        guard.record_outcome(-0.5);
+        assert_eq!(guard.losses(), 3);
+        assert!(!guard.entries_allowed());
+
+        guard.record_outcome(1.0);
+        assert!(guard.entries_allowed());
+    }
+}
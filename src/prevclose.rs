@@ -0,0 +1,43 @@
+// Previous close (prevclose.rs), the close to close change the difference based
+// indicators are built on, so each doesn't special case the first candle itself.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PrevClose {
+    prev: Option<f64>,
+}
+
+impl PrevClose {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        PrevClose::default()
+    }
+
+    // Close of the previous candle.
+    pub fn prev(&self) -> Option<f64> {
+        self.prev
+    }
+
+    // Record the close, returning the change from the previous one. None on the
+    // first candle.
+    pub fn update(&mut self, close_price: f64) -> Option<f64> {
+        let change = self.prev.map(|prev| close_price - prev);
+        self.prev = Some(close_price);
+        change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes() {
+        let mut pc = PrevClose::new();
+        assert_eq!(pc.prev(), None);
+        assert_eq!(pc.update(10.0), None);
+        assert_eq!(pc.prev(), Some(10.0));
+        assert_eq!(pc.update(12.5), Some(2.5));
+        assert_eq!(pc.update(11.0), Some(-1.5));
+        assert_eq!(pc.update(11.0), Some(0.0));
+    }
+}
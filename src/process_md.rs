@@ -1,45 +1,428 @@
 // Process market data (process_md.rs).
 use crate::account_manager;
+use crate::adx;
 use crate::binance;
 use crate::candlestick;
 use crate::config;
+use crate::error;
+use crate::hysteresis;
 use crate::ma;
 use crate::order;
+use crate::percentile;
 use crate::position;
+use crate::rsi;
+use crate::stc;
 use crate::tradingpair;
+use crate::window;
 
-use math::round;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::mpsc::Sender;
 use std::{thread, time::Duration};
 use websocket::{stream::sync::NetworkStream, sync::Client, ClientBuilder, OwnedMessage};
 
 use serde_json;
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
 use account_manager::{AccountManager, OrderQuantity};
 use binance::Binance;
 use config::{ExchangeConfig, StrategyConfig};
+use error::CtError;
+use hysteresis::Hysteresis;
 use position::PositionType;
 use tradingpair::TradingPair;
+use window::RollingWindow;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum TradeSignal {
     MaCross,
     MaTrendReversal,
     MACD,
+    Stc,
 }
 
-#[derive(Debug)]
+impl fmt::Display for TradeSignal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for TradeSignal {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "MaCross" => Ok(TradeSignal::MaCross),
+            "MaTrendReversal" => Ok(TradeSignal::MaTrendReversal),
+            "MACD" => Ok(TradeSignal::MACD),
+            "Stc" => Ok(TradeSignal::Stc),
+            _ => Err(CtError::Parse {
+                input: s.to_string(),
+            }),
+        }
+    }
+}
+
+// A signal fired on a symbol, displayed as "BTCUSDT MaCross LONG @ 101.5".
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalEvent {
+    pub symbol: String,
+    pub kind: TradeSignal,
+    pub direction: PositionType,
+    pub price: f64,
+}
+
+impl fmt::Display for SignalEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} @ {}",
+            self.symbol, self.kind, self.direction, self.price
+        )
+    }
+}
+
+impl FromStr for SignalEvent {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || CtError::Parse {
+            input: s.to_string(),
+        };
+
+        match s.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [symbol, kind, direction, "@", price] => Ok(SignalEvent {
+                symbol: symbol.to_string(),
+                kind: kind.parse()?,
+                direction: direction.parse()?,
+                price: price.parse().map_err(|_| err())?,
+            }),
+            _ => Err(err()),
+        }
+    }
+}
+
+// How close prices are transformed before being fed to the signal indicators, so
+// the same thresholds work for pairs at very different price levels.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NormalizeMode {
+    None,
+    // Percentage change from the previous close.
+    PercentChange,
+    // Standard deviations from the mean of recent closes.
+    ZScore,
+}
+
+// What on_candle does with a candle which doesn't follow on from the previous one.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GapPolicy {
+    // Process it as if it did.
+    Skip,
+    // Fill any missing candles with the previous close before processing it.
+    ForwardFill,
+    // Reject it.
+    Error,
+}
+
+// Whether signals are only checked on closed candles, or also on the forming
+// candle where they are provisional and may revert before it closes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SignalTiming {
+    OnClose,
+    #[allow(dead_code)]
+    Intrabar,
+}
+
+// When set, a signal isn't acted on its own candle but on the next, and only if
+// that candle confirms it. Close needs the next close beyond the signal candle's
+// close, Breakout beyond its high for a long or low for a short.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DeferredEntry {
+    #[allow(dead_code)]
+    Close,
+    #[allow(dead_code)]
+    Breakout,
+}
+
+// A deferred signal waiting on the next candle, with its candle's prices.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct PendingEntry {
+    side: PositionType,
+    close: f64,
+    high: f64,
+    low: f64,
+}
+
+impl PendingEntry {
+    fn confirmed_by(&self, mode: DeferredEntry, closing_price: f64) -> bool {
+        let (above, below) = match mode {
+            DeferredEntry::Close => (self.close, self.close),
+            DeferredEntry::Breakout => (self.high, self.low),
+        };
+
+        match self.side {
+            PositionType::Long => closing_price > above,
+            PositionType::Short => closing_price < below,
+            PositionType::None => false,
+        }
+    }
+}
+
+// Every indicator's value after a candle, for replaying and debugging signals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndicatorSnapshot {
+    pub candle_count: u64,
+    pub close: Option<f64>,
+    pub slow_ma: Option<f64>,
+    pub fast_ma: Option<f64>,
+    pub macd: Option<f64>,
+    pub macd_signal: Option<f64>,
+    pub adx: Option<f64>,
+    pub stc: Option<f64>,
+    pub rsi: Option<f64>,
+    pub stoch_rsi: Option<f64>,
+    pub desired_position: PositionType,
+}
+
+// A signal and whether it came from a candle which hasn't closed yet.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimedSignal {
+    pub position: PositionType,
+    pub provisional: bool,
+}
+
+// Which trade signals may run, a disabled signal produces no position and its
+// indicators aren't computed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StrategyMask {
+    pub ma_cross: bool,
+    pub ma_trend_reversal: bool,
+    pub macd: bool,
+    pub stc: bool,
+}
+
+impl Default for StrategyMask {
+    fn default() -> Self {
+        StrategyMask {
+            ma_cross: true,
+            ma_trend_reversal: true,
+            macd: true,
+            stc: true,
+        }
+    }
+}
+
+impl StrategyMask {
+    // Only the given signal enabled.
+    #[allow(dead_code)]
+    pub fn only(signal: TradeSignal) -> Self {
+        StrategyMask {
+            ma_cross: signal == TradeSignal::MaCross,
+            ma_trend_reversal: signal == TradeSignal::MaTrendReversal,
+            macd: signal == TradeSignal::MACD,
+            stc: signal == TradeSignal::Stc,
+        }
+    }
+
+    pub fn enabled(&self, signal: TradeSignal) -> bool {
+        match signal {
+            TradeSignal::MaCross => self.ma_cross,
+            TradeSignal::MaTrendReversal => self.ma_trend_reversal,
+            TradeSignal::MACD => self.macd,
+            TradeSignal::Stc => self.stc,
+        }
+    }
+}
+
+// Priority of each trade signal when several fire on the same candle, higher
+// wins. All equal by default.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct StrategyPriority {
+    pub ma_cross: u8,
+    pub ma_trend_reversal: u8,
+    pub macd: u8,
+    pub stc: u8,
+}
+
+impl StrategyPriority {
+    pub fn priority(&self, signal: TradeSignal) -> u8 {
+        match signal {
+            TradeSignal::MaCross => self.ma_cross,
+            TradeSignal::MaTrendReversal => self.ma_trend_reversal,
+            TradeSignal::MACD => self.macd,
+            TradeSignal::Stc => self.stc,
+        }
+    }
+
+    // The direction of the highest priority signals, None if there are none or
+    // they disagree. Signals without a direction are ignored.
+    #[allow(dead_code)]
+    pub fn resolve(&self, signals: &[(TradeSignal, PositionType)]) -> PositionType {
+        let directional = signals
+            .iter()
+            .filter(|(_, position)| *position != PositionType::None);
+        let top = match directional.clone().map(|(s, _)| self.priority(*s)).max() {
+            Some(top) => top,
+            None => return PositionType::None,
+        };
+
+        let mut resolved = PositionType::None;
+        for (_, position) in directional.filter(|(s, _)| self.priority(*s) == top) {
+            if resolved != PositionType::None && resolved != *position {
+                return PositionType::None;
+            }
+            resolved = *position;
+        }
+
+        resolved
+    }
+}
+
+// Snapshot of a higher timeframe tracker's trend, its fast MA above or below its
+// slow MA. Only signals with the trend are permitted, none while it's unknown.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HigherTimeframeFilter {
+    pub trend: PositionType,
+}
+
+impl HigherTimeframeFilter {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        HigherTimeframeFilter {
+            trend: PositionType::None,
+        }
+    }
+
+    // Take the trend from the higher timeframe tracker, after each of its candles.
+    #[allow(dead_code)]
+    pub fn update(&mut self, higher: &MarketDataTracker) {
+        self.trend = match (higher.fast_ma_data.latest(), higher.slow_ma_data.latest()) {
+            (Some(fast), Some(slow)) if fast > slow => PositionType::Long,
+            (Some(fast), Some(slow)) if fast < slow => PositionType::Short,
+            _ => PositionType::None,
+        };
+    }
+
+    pub fn permits(&self, signal: PositionType) -> bool {
+        signal == PositionType::None || signal == self.trend
+    }
+}
+
+// Indicators updated by on_candle.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Indicator {
+    // Those required by the configured trade signal.
+    Signal,
+    Adx,
+    Rsi,
+    StochRsi,
+}
+
+impl Indicator {
+    // Indicators whose current value this one is computed from.
+    fn deps(self) -> &'static [Indicator] {
+        match self {
+            Indicator::StochRsi => &[Indicator::Rsi],
+            _ => &[],
+        }
+    }
+}
+
+// Order the indicators so each is updated after those it depends on, so it always
+// reads their value for the current candle. Dependencies are added if missing,
+// otherwise the given order is kept.
+pub fn update_order(indicators: &[Indicator]) -> Vec<Indicator> {
+    fn visit(indicator: Indicator, visiting: &mut Vec<Indicator>, order: &mut Vec<Indicator>) {
+        if order.contains(&indicator) {
+            return;
+        }
+        if visiting.contains(&indicator) {
+            panic!("Indicator dependency cycle through {:?}", indicator);
+        }
+
+        visiting.push(indicator);
+        for dep in indicator.deps() {
+            visit(*dep, visiting, order);
+        }
+        visiting.pop();
+
+        order.push(indicator);
+    }
+
+    let mut order = Vec::with_capacity(indicators.len());
+    let mut visiting = Vec::new();
+    for indicator in indicators {
+        visit(*indicator, &mut visiting, &mut order);
+    }
+
+    order
+}
+
+#[derive(Debug, Clone)]
 pub struct MarketDataTracker {
     pub slow_ma_data: ma::MAData,
     pub fast_ma_data: ma::MAData,
+    // How the MA cross treats floored MAs which are exactly equal.
+    pub ma_tie_policy: ma::TreatEqualAs,
     pub macd: ma::MACD,
+    pub adx: adx::Adx,
+    pub stc: stc::Stc,
+    pub rsi: rsi::Rsi,
+    pub stoch_rsi: rsi::StochRsi,
+
+    // When set, ranks each RSI value against its own recent history.
+    pub rsi_percentile: Option<percentile::PercentRank>,
 
+    // When set, RSI levels firing long and short signals, see rsi_band_signal().
+    pub rsi_long_band: Option<Hysteresis>,
+    pub rsi_short_band: Option<Hysteresis>,
+    rsi_band_signal: PositionType,
+
+    // Order on_candle updates the indicators in, see update_order().
+    pub update_order: Vec<Indicator>,
+
+    // Direction of the last signal produced, repeated signals in the same
+    // direction don't change it.
     pub desired_position: PositionType,
 
+    // Number of candles processed, and the candle numbers at which the last
+    // long and short signals changed the desired position.
+    pub candle_count: u64,
+    pub last_long_index: Option<u64>,
+    pub last_short_index: Option<u64>,
+
     // The signal type we are looking for.
     pub trade_signal: TradeSignal,
+    pub strategy_mask: StrategyMask,
+    pub signal_timing: SignalTiming,
+    pub deferred_entry: Option<DeferredEntry>,
+    // Flip the strategy's signals, longs become shorts and shorts longs.
+    pub contrarian: bool,
+    pending_entry: Option<PendingEntry>,
+    // Where signals are sent as well as returned, see set_signal_sink().
+    signal_sink: Option<Sender<SignalEvent>>,
+
+    // When set, no signals are produced on candles trading less than this volume.
+    pub min_volume: Option<f64>,
+    // Volume of the latest candle, None when the latest close came from compute().
+    latest_volume: Option<f64>,
+    // When set, only signals with the higher timeframe's trend are produced.
+    pub higher_timeframe: Option<HigherTimeframeFilter>,
+    // When set, no signals are produced after a run of losing trades.
+    pub loss_streak_guard: Option<position::LossStreakGuard>,
+    // When set, no signals are produced once equity draws down too far.
+    pub circuit_breaker: Option<position::CircuitBreaker>,
+    // When set, at most one entry every this many candles, whichever strategy
+    // produced it.
+    pub min_bars_between_entries: Option<u64>,
+    // Candle number of the last entry, the last signal which changed the desired
+    // position.
+    pub last_entry_bar: Option<u64>,
 
     // Previous candles, green or red?
     pub candle_color_history: Vec<candlestick::CandleColor>,
@@ -53,6 +436,9 @@ pub struct MarketDataTracker {
     // Market/Limit?
     pub order_type: order::OrderType,
 
+    // How a signal against the current position is executed.
+    pub flip_policy: order::FlipPolicy,
+
     // % Away from the last close price we'll accept for a limit order.
     pub limit_offset: Option<u8>,
 
@@ -67,14 +453,652 @@ pub struct MarketDataTracker {
     // Same goes in the reverse direction for red candles.
     pub confirmation_candles: Option<u8>,
 
+    // If trade_signal is TradeSignal::MaTrendReversal, the percentage the fast MA
+    // must move on both sides of a turn for it to count.
+    pub trend_change_min_pct: Option<f64>,
+
     // If we are using the macd as the primary indicator we might also have a
     // trend MA we need to be above in order to take a long position.
     pub macd_trend_ma: ma::MAData,
+
+    // Close price and time (ms since the epoch) of the last candle processed.
+    pub last_close: Option<f64>,
+    pub last_close_time: Option<u64>,
+
+    // Symbol of the trading pair tracked, when set candles for other symbols are
+    // rejected.
+    pub symbol: Option<String>,
+
+    // Price on_candle feeds the signal indicators, and the last price fed to them.
+    pub price_source: candlestick::PriceSource,
+    last_input: Option<f64>,
+
+    // Drop candles with a NaN or inf price before they reach any indicator, so
+    // the indicators stay in step. Otherwise each indicator ignores the bad value
+    // on its own.
+    pub reject_non_finite: bool,
+
+    // Interval between candles, when set each candle is checked to follow on from
+    // the previous one and gaps are handled by the gap policy.
+    pub candle_interval: Option<Duration>,
+    pub gap_policy: GapPolicy,
+    prev_candle: Option<candlestick::Candle>,
+
+    // Transform applied to close prices before computing the signal indicators,
+    // and the recent closes used for NormalizeMode::ZScore.
+    pub normalize: NormalizeMode,
+    normalize_window: RollingWindow<f64>,
+}
+
+impl MarketDataTracker {
+    pub fn new(
+        trade_signal: TradeSignal,
+        slow_ma: Option<u16>,
+        fast_ma: Option<u16>,
+        ema: bool,
+    ) -> Self {
+        MarketDataTracker {
+            slow_ma_data: ma::MAData::new(slow_ma.unwrap_or(0)),
+            fast_ma_data: ma::MAData::new(fast_ma.unwrap_or(0)),
+            ma_tie_policy: ma::TreatEqualAs::default(),
+            macd: ma::MACD::new(),
+            adx: adx::Adx::new(DEFAULT_ADX_PERIOD),
+            stc: stc::Stc::new(),
+            rsi: rsi::Rsi::new(DEFAULT_RSI_PERIOD),
+            stoch_rsi: rsi::StochRsi::new(DEFAULT_RSI_PERIOD),
+            rsi_percentile: None,
+            rsi_long_band: None,
+            rsi_short_band: None,
+            rsi_band_signal: PositionType::None,
+            update_order: update_order(&[Indicator::Signal, Indicator::Adx, Indicator::StochRsi]),
+            desired_position: PositionType::None,
+            candle_count: 0,
+            last_long_index: None,
+            last_short_index: None,
+            trade_signal,
+            strategy_mask: StrategyMask::default(),
+            signal_timing: SignalTiming::OnClose,
+            deferred_entry: None,
+            contrarian: false,
+            pending_entry: None,
+            signal_sink: None,
+            min_volume: None,
+            latest_volume: None,
+            higher_timeframe: None,
+            loss_streak_guard: None,
+            circuit_breaker: None,
+            min_bars_between_entries: None,
+            last_entry_bar: None,
+            candle_color_history: Vec::new(),
+            ema,
+            bvlt: false,
+            order_type: order::OrderType::Market,
+            flip_policy: order::FlipPolicy::default(),
+            limit_offset: None,
+            stop_percent: None,
+            take_profit_percent: None,
+            confirmation_candles: None,
+            trend_change_min_pct: None,
+            macd_trend_ma: ma::MAData::new(0),
+            last_close: None,
+            last_close_time: None,
+            symbol: None,
+            price_source: candlestick::PriceSource::Close,
+            last_input: None,
+            reject_non_finite: true,
+            candle_interval: None,
+            gap_policy: GapPolicy::Skip,
+            prev_candle: None,
+            normalize: NormalizeMode::None,
+            normalize_window: RollingWindow::new(ZSCORE_WINDOW),
+        }
+    }
+
+    // Update the TA indicators required by the configured signal.
+    pub fn compute(&mut self, closing_price: f64) {
+        if self.reject_non_finite && !closing_price.is_finite() {
+            warn!("Ignoring non-finite closing price {}", closing_price);
+            return;
+        }
+
+        let prev_input = self.last_input;
+        self.candle_count += 1;
+        self.latest_volume = None;
+        self.last_input = Some(closing_price);
+
+        if !self.strategy_mask.enabled(self.trade_signal) {
+            return;
+        }
+
+        let closing_price = match self.normalized(closing_price, prev_input) {
+            Some(closing_price) => closing_price,
+            None => return,
+        };
+
+        match self.trade_signal {
+            TradeSignal::MaCross => {
+                self.slow_ma_data.compute(closing_price, self.ema);
+                self.fast_ma_data.compute(closing_price, self.ema);
+            }
+            TradeSignal::MaTrendReversal => {
+                self.fast_ma_data.compute(closing_price, self.ema);
+            }
+            TradeSignal::MACD => {
+                self.macd.compute(closing_price);
+
+                if self.macd_trend_ma.num_candles > 0 {
+                    self.macd_trend_ma.compute(closing_price, self.ema);
+                }
+            }
+            TradeSignal::Stc => {
+                self.stc.compute(closing_price);
+            }
+        }
+    }
+
+    // Close price transformed by the normalize mode, None until there's enough
+    // history to transform it.
+    fn normalized(&mut self, closing_price: f64, prev_input: Option<f64>) -> Option<f64> {
+        match self.normalize {
+            NormalizeMode::None => Some(closing_price),
+            NormalizeMode::PercentChange => match prev_input {
+                Some(prev_input) if prev_input != 0.0 => {
+                    Some(100.0 * (closing_price - prev_input) / prev_input)
+                }
+                _ => None,
+            },
+            NormalizeMode::ZScore => {
+                self.normalize_window.push(closing_price);
+                if !self.normalize_window.is_full() {
+                    return None;
+                }
+
+                let n = self.normalize_window.len() as f64;
+                let mean = self.normalize_window.sum() / n;
+                let variance = self
+                    .normalize_window
+                    .iter()
+                    .map(|cp| (cp - mean).powi(2))
+                    .sum::<f64>()
+                    / n;
+                if variance > 0.0 {
+                    Some((closing_price - mean) / variance.sqrt())
+                } else {
+                    Some(0.0)
+                }
+            }
+        }
+    }
+
+    // Check the configured signal against the latest TA, updating the desired
+    // position when the signal changes direction.
+    pub fn signal(&mut self, tp: &TradingPair, closing_price: f64) -> PositionType {
+        if !self.strategy_mask.enabled(self.trade_signal) {
+            return PositionType::None;
+        }
+
+        if let Some(breaker) = &self.circuit_breaker {
+            if breaker.tripped() {
+                return PositionType::None;
+            }
+        }
+
+        let decision = match self.trade_signal {
+            TradeSignal::MaCross => ma::trading_decision_ma_cross(tp, self, closing_price),
+            TradeSignal::MaTrendReversal => {
+                ma::trading_decision_ma_trend_change(tp, self, closing_price)
+            }
+            TradeSignal::MACD => ma::trading_decision_macd(tp, self, closing_price),
+            TradeSignal::Stc => stc::trading_decision_stc(tp, self, closing_price),
+        };
+        let decision = if self.contrarian {
+            decision.inverse()
+        } else {
+            decision
+        };
+        let decision = self.defer(decision, closing_price);
+
+        if decision != PositionType::None {
+            if let Some(min_volume) = self.min_volume {
+                // Without a candle the volume is unknown, treat it as too thin.
+                let volume = self.latest_volume;
+                if !volume.is_some_and(|v| v >= min_volume) {
+                    info!(
+                        "{} {:?} signal blocked, volume {:?} below {}",
+                        tp.symbol(),
+                        decision,
+                        volume,
+                        min_volume
+                    );
+                    return PositionType::None;
+                }
+            }
+
+            if let Some(filter) = &self.higher_timeframe {
+                if !filter.permits(decision) {
+                    info!(
+                        "{} {:?} signal blocked, the higher timeframe trend is {}",
+                        tp.symbol(),
+                        decision,
+                        filter.trend
+                    );
+                    return PositionType::None;
+                }
+            }
+
+            if let Some(guard) = &self.loss_streak_guard {
+                if !guard.entries_allowed() {
+                    info!(
+                        "{} {:?} signal blocked after {} losses in a row",
+                        tp.symbol(),
+                        decision,
+                        guard.losses()
+                    );
+                    return PositionType::None;
+                }
+            }
+
+            if let (Some(min_bars), Some(last_entry_bar)) =
+                (self.min_bars_between_entries, self.last_entry_bar)
+            {
+                let bars = self.candle_count - last_entry_bar;
+                if bars < min_bars {
+                    info!(
+                        "{} {:?} signal blocked, {} candles since the last entry",
+                        tp.symbol(),
+                        decision,
+                        bars
+                    );
+                    return PositionType::None;
+                }
+            }
+        }
+
+        if decision != PositionType::None {
+            if let Some(sink) = &self.signal_sink {
+                let event = SignalEvent {
+                    symbol: tp.symbol().to_string(),
+                    kind: self.trade_signal,
+                    direction: decision,
+                    price: closing_price,
+                };
+                if sink.send(event).is_err() {
+                    warn!("Signal sink disconnected, no longer sending signals");
+                    self.signal_sink = None;
+                }
+            }
+        }
+
+        if decision != PositionType::None && decision != self.desired_position {
+            self.desired_position = decision;
+            self.last_entry_bar = Some(self.candle_count);
+            if decision == PositionType::Long {
+                self.last_long_index = Some(self.candle_count);
+            } else {
+                self.last_short_index = Some(self.candle_count);
+            }
+        }
+
+        decision
+    }
+
+    // With a deferred entry mode, hold back this candle's signal until the next and
+    // return the previous candle's signal if this candle confirms it.
+    fn defer(&mut self, decision: PositionType, closing_price: f64) -> PositionType {
+        let mode = match self.deferred_entry {
+            Some(mode) => mode,
+            None => return decision,
+        };
+
+        let confirmed = match self.pending_entry.take() {
+            Some(pending) if pending.confirmed_by(mode, closing_price) => pending.side,
+            _ => PositionType::None,
+        };
+
+        if decision != PositionType::None {
+            // The signal candle's range, if it came in through on_candle().
+            let (high, low) = match &self.prev_candle {
+                Some(candle) if candle.close == closing_price => (candle.high, candle.low),
+                _ => (closing_price, closing_price),
+            };
+            self.pending_entry = Some(PendingEntry {
+                side: decision,
+                close: closing_price,
+                high,
+                low,
+            });
+        }
+
+        confirmed
+    }
+
+    // Signal the forming candle would give if it closed now, flagged provisional.
+    // The tracker itself is left untouched. None unless the signal timing is
+    // SignalTiming::Intrabar or the candle is rejected.
+    pub fn peek_signal(
+        &self,
+        tp: &TradingPair,
+        forming: &candlestick::Candle,
+    ) -> Option<TimedSignal> {
+        if self.signal_timing != SignalTiming::Intrabar {
+            return None;
+        }
+
+        let mut peek = self.clone();
+        // Provisional signals aren't sent.
+        peek.signal_sink = None;
+        peek.try_on_candle(forming).ok()?;
+
+        Some(TimedSignal {
+            position: peek.signal(tp, forming.close),
+            provisional: true,
+        })
+    }
+
+    // Every indicator value available, keyed by a stable name such as "ma.fast" or
+    // "macd.histogram", for exporting as metrics. Indicators still warming up are
+    // left out.
+    #[allow(dead_code)]
+    pub fn metrics(&self) -> HashMap<String, f64> {
+        let values = [
+            ("close", self.last_close),
+            ("ma.slow", self.slow_ma_data.latest()),
+            ("ma.fast", self.fast_ma_data.latest()),
+            ("ma.macd_trend", self.macd_trend_ma.latest()),
+            ("macd.line", self.macd.macd_latest),
+            ("macd.signal", self.macd.signal.latest()),
+            ("macd.histogram", self.macd.distance_from_signal()),
+            ("adx", self.adx.latest()),
+            ("adx.plus_di", self.adx.plus_di()),
+            ("adx.minus_di", self.adx.minus_di()),
+            ("stc", self.stc.latest()),
+            ("rsi", self.rsi.latest()),
+            ("rsi.smoothed", self.rsi.smoothed_latest()),
+            (
+                "rsi.percentile",
+                self.rsi_percentile.as_ref().and_then(|rank| rank.latest()),
+            ),
+            ("stoch_rsi", self.stoch_rsi.latest()),
+        ];
+
+        values
+            .iter()
+            .filter_map(|(key, value)| value.map(|value| (key.to_string(), value)))
+            .collect()
+    }
+
+    // Current value of every indicator.
+    #[allow(dead_code)]
+    pub fn snapshot(&self) -> IndicatorSnapshot {
+        IndicatorSnapshot {
+            candle_count: self.candle_count,
+            close: self.last_close,
+            slow_ma: self.slow_ma_data.latest(),
+            fast_ma: self.fast_ma_data.latest(),
+            macd: self.macd.macd_latest,
+            macd_signal: self.macd.signal.latest(),
+            adx: self.adx.latest(),
+            stc: self.stc.latest(),
+            rsi: self.rsi.latest(),
+            stoch_rsi: self.stoch_rsi.latest(),
+            desired_position: self.desired_position,
+        }
+    }
+
+    // Record the realised profit or loss of a closed trade with the loss streak
+    // guard, if there is one.
+    #[allow(dead_code)]
+    pub fn record_outcome(&mut self, pnl: f64) {
+        if let Some(guard) = self.loss_streak_guard.as_mut() {
+            guard.record_outcome(pnl);
+        }
+    }
+
+    // Record the account value with the circuit breaker, if there is one. When it
+    // trips the desired position is forced flat and no more signals are produced
+    // until the breaker is reset.
+    #[allow(dead_code)]
+    pub fn record_equity(&mut self, value: f64) {
+        if let Some(breaker) = self.circuit_breaker.as_mut() {
+            if breaker.record_equity(value) {
+                warn!(
+                    "Circuit breaker tripped, {}% drawdown limit exceeded, forcing flat",
+                    breaker.max_drawdown_pct
+                );
+                self.desired_position = PositionType::None;
+            }
+        }
+    }
+
+    // Whether the indicators the strategy needs all have values, so its signal can
+    // fire.
+    pub fn strategy_ready(&self, strategy: TradeSignal) -> bool {
+        match strategy {
+            TradeSignal::MaCross => {
+                self.slow_ma_data.latest().is_some() && self.fast_ma_data.penultimate().is_some()
+            }
+            TradeSignal::MaTrendReversal => self.fast_ma_data.penultimate_penultimate().is_some(),
+            TradeSignal::MACD => {
+                self.macd.macd_previous.is_some()
+                    && self.macd.signal.latest().is_some()
+                    && (self.macd_trend_ma.num_candles == 0
+                        || self.macd_trend_ma.penultimate().is_some())
+            }
+            TradeSignal::Stc => self.stc.previous().is_some(),
+        }
+    }
+
+    // Whether every indicator on_candle updates has a value, see strategy_ready()
+    // for the configured strategy.
+    #[allow(dead_code)]
+    pub fn all_ready(&self) -> bool {
+        self.update_order.iter().all(|indicator| match indicator {
+            Indicator::Signal => self.strategy_ready(self.trade_signal),
+            Indicator::Adx => self.adx.latest().is_some(),
+            Indicator::Rsi => self.rsi.latest().is_some(),
+            Indicator::StochRsi => self.stoch_rsi.latest().is_some(),
+        })
+    }
+
+    // Also send every signal produced to 'sender', e.g. for an execution layer
+    // running on another thread. Replaces any previous sink, which is dropped if
+    // its receiver goes away.
+    #[allow(dead_code)]
+    pub fn set_signal_sink(&mut self, sender: Sender<SignalEvent>) {
+        self.signal_sink = Some(sender);
+    }
+
+    // Forget previous signals, re-arming them, while leaving the indicators warmed
+    // up, e.g. after a manual trade.
+    #[allow(dead_code)]
+    pub fn reset_signals(&mut self) {
+        self.desired_position = PositionType::None;
+        self.last_long_index = None;
+        self.last_short_index = None;
+        self.last_entry_bar = None;
+        self.pending_entry = None;
+    }
+
+    // Number of candles processed since a signal in the given direction last
+    // changed the desired position.
+    #[allow(dead_code)]
+    pub fn candles_since(&self, position: PositionType) -> Option<u64> {
+        let index = match position {
+            PositionType::Long => self.last_long_index,
+            PositionType::Short => self.last_short_index,
+            PositionType::None => None,
+        }?;
+
+        Some(self.candle_count - index)
+    }
+
+    // Update all indicators, including those which need more than the close
+    // price, with a closed candle.
+    pub fn on_candle(&mut self, candle: &candlestick::Candle) {
+        if let Err(e) = self.try_on_candle(candle) {
+            warn!("Ignoring candle {:?}, {}", candle, e);
+        }
+    }
+
+    // As on_candle() but returning why a candle was rejected.
+    pub fn try_on_candle(&mut self, candle: &candlestick::Candle) -> Result<(), CtError> {
+        if let (Some(expected), Some(actual)) = (&self.symbol, &candle.symbol) {
+            if expected != actual {
+                return Err(CtError::SymbolMismatch {
+                    expected: expected.clone(),
+                    actual: actual.clone(),
+                });
+            }
+        }
+
+        if self.reject_non_finite
+            && ![candle.open, candle.high, candle.low, candle.close]
+                .iter()
+                .all(|v| v.is_finite())
+        {
+            return Err(CtError::NonFinitePrice);
+        }
+
+        if let (Some(interval), Some(prev)) = (self.candle_interval, self.prev_candle.clone()) {
+            match candlestick::validate_continuity(&prev, candle, interval) {
+                Ok(()) => {}
+                Err(CtError::CandleGap { missing })
+                    if self.gap_policy == GapPolicy::ForwardFill =>
+                {
+                    let interval = interval.as_millis() as u64;
+                    for i in 1..=missing {
+                        let open_time = prev.open_time + i * interval;
+                        self.apply_candle(&candlestick::Candle {
+                            open_time,
+                            close_time: open_time + interval - 1,
+                            open: prev.close,
+                            high: prev.close,
+                            low: prev.close,
+                            close: prev.close,
+                            volume: 0.0,
+                            symbol: prev.symbol.clone(),
+                        });
+                    }
+                }
+                Err(e) => {
+                    if self.gap_policy != GapPolicy::Skip {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        self.apply_candle(candle);
+        Ok(())
+    }
+
+    fn apply_candle(&mut self, candle: &candlestick::Candle) {
+        let order = std::mem::take(&mut self.update_order);
+        for indicator in order.iter() {
+            self.update(*indicator, candle);
+        }
+        self.update_order = order;
+
+        self.last_close = Some(candle.close);
+        self.last_close_time = Some(candle.close_time);
+        self.prev_candle = Some(candle.clone());
+        self.latest_volume = Some(candle.volume);
+    }
+
+    fn update_rsi_bands(&mut self) {
+        self.rsi_band_signal = PositionType::None;
+        let rsi = match self.rsi.latest() {
+            Some(rsi) => rsi,
+            None => return,
+        };
+
+        if self
+            .rsi_long_band
+            .as_mut()
+            .is_some_and(|band| band.update(rsi))
+        {
+            self.rsi_band_signal = PositionType::Long;
+        }
+        if self
+            .rsi_short_band
+            .as_mut()
+            .is_some_and(|band| band.update(rsi))
+        {
+            self.rsi_band_signal = PositionType::Short;
+        }
+    }
+
+    // Signal fired by the RSI hysteresis bands on the latest candle, None if
+    // neither band fired.
+    #[allow(dead_code)]
+    pub fn rsi_band_signal(&self) -> PositionType {
+        self.rsi_band_signal
+    }
+
+    fn update(&mut self, indicator: Indicator, candle: &candlestick::Candle) {
+        match indicator {
+            Indicator::Signal => self.compute(candle.price(self.price_source)),
+            Indicator::Adx => self.adx.compute(candle.high, candle.low, candle.close),
+            Indicator::Rsi => {
+                self.rsi.compute(candle.close);
+                if let (Some(rank), Some(rsi)) = (self.rsi_percentile.as_mut(), self.rsi.latest()) {
+                    rank.compute(rsi);
+                }
+                self.update_rsi_bands();
+            }
+            Indicator::StochRsi => {
+                if let Some(rsi) = self.rsi.latest() {
+                    self.stoch_rsi.compute(rsi);
+                }
+            }
+        }
+    }
+
+    // Check the last candle processed closed no more than 'max_age' ago, a stalled
+    // feed otherwise leaves indicators reporting stale values that look valid.
+    #[allow(dead_code)]
+    pub fn data_is_fresh(&self, now: DateTime<Utc>, max_age: Duration) -> bool {
+        match self.last_close_time {
+            Some(close_time) => {
+                now.timestamp_millis() - (close_time as i64) <= max_age.as_millis() as i64
+            }
+            None => false,
+        }
+    }
+
+    // Consume closed candles from any source, updating all indicators. Returns the
+    // number of candles processed.
+    #[allow(dead_code)]
+    pub fn feed_from_iter(&mut self, iter: impl IntoIterator<Item = candlestick::Candle>) -> usize {
+        let mut processed = 0;
+        for candle in iter {
+            self.on_candle(&candle);
+            processed += 1;
+        }
+
+        processed
+    }
+
+    // Prime the indicators from recent closed candles, oldest first, leaving the
+    // tracker as if they had streamed in.
+    #[allow(dead_code)]
+    pub fn warmup(&mut self, history: &[candlestick::Candle]) {
+        for candle in history {
+            self.on_candle(candle);
+        }
+    }
 }
 
 // The number of ticks away from the last closing price that we will accept.
 static DEFAULT_LIMIT_RANGE: u8 = 2;
 
+// Wilder's default ADX and RSI periods.
+static DEFAULT_ADX_PERIOD: u16 = 14;
+static DEFAULT_RSI_PERIOD: u16 = 14;
+
+// Number of closes NormalizeMode::ZScore is computed over.
+static ZSCORE_WINDOW: usize = 20;
+
 // Check & update if the last required number of candles are all green or all red.
 fn trade_confirmation_via_previous_candles(
     mt: &mut MarketDataTracker,
@@ -126,13 +1150,7 @@ fn trading_decision(
     let mut decision = PositionType::None;
 
     if trading_pair.get_bvlt_type().is_none() {
-        decision = match mt.trade_signal {
-            TradeSignal::MaCross => ma::trading_decision_ma_cross(&trading_pair, mt, closing_price),
-            TradeSignal::MaTrendReversal => {
-                ma::trading_decision_ma_trend_change(&trading_pair, mt, closing_price)
-            }
-            TradeSignal::MACD => ma::trading_decision_macd(&trading_pair, mt, closing_price),
-        };
+        decision = mt.signal(trading_pair, closing_price);
 
         // Update the list of previous candle colours and return if we've matched a number in
         // a row which are the same colour.
@@ -205,10 +1223,14 @@ fn trading_decision(
             decision = PositionType::None;
         } else if (confirmed || take_profit_override) && decision != PositionType::None {
             info!(
-                "{:#?} trade decision changed: {:#?} --> {:#?}",
-                trading_pair.symbol(),
+                "trade decision changed from {}: {}",
                 cur_position_type,
-                decision
+                SignalEvent {
+                    symbol: trading_pair.symbol().to_string(),
+                    kind: mt.trade_signal,
+                    direction: decision,
+                    price: closing_price,
+                }
             );
         }
     }
@@ -222,27 +1244,14 @@ fn process_close_data(
     am: &AccountManager,
     trading_pair: &TradingPair,
     mt: &mut MarketDataTracker,
-    closing_price: f64,
+    candle: &candlestick::Candle,
     prev_closing_price: Option<f64>,
     place_trades: bool,
 ) {
-    // Compute the various technical indicators.
-    match mt.trade_signal {
-        TradeSignal::MaCross => {
-            mt.slow_ma_data.compute(closing_price, mt.ema);
-            mt.fast_ma_data.compute(closing_price, mt.ema);
-        }
-        TradeSignal::MaTrendReversal => {
-            mt.fast_ma_data.compute(closing_price, mt.ema);
-        }
-        TradeSignal::MACD => {
-            mt.macd.compute(closing_price);
+    let closing_price = candle.close;
 
-            if mt.macd_trend_ma.num_candles > 0 {
-                mt.macd_trend_ma.compute(closing_price, mt.ema);
-            }
-        }
-    }
+    // Compute the various technical indicators.
+    mt.on_candle(candle);
 
     if !place_trades {
         // If we just want to process the data then return now.
@@ -258,26 +1267,13 @@ fn process_close_data(
         PositionType::Short | PositionType::Long => {
             // Compute the limit prices we are willing to accept for BUY/SELL orders.
             let limit_price = if mt.order_type == order::OrderType::Limit {
-                let tick_increment = trading_pair.get_tick_size();
-                if decision == PositionType::Long {
-                    Some(round::floor(
-                        closing_price
-                            + (tick_increment
-                                * mt.limit_offset
-                                    .expect("limit offset is None but this is a limit order")
-                                    as f64),
-                        trading_pair.get_price_dps(),
-                    ))
-                } else {
-                    Some(round::floor(
-                        closing_price
-                            - (tick_increment
-                                * mt.limit_offset
-                                    .expect("limit offset is None but this is a limit order")
-                                    as f64),
-                        trading_pair.get_price_dps(),
-                    ))
-                }
+                Some(order::limit_price(
+                    trading_pair,
+                    decision,
+                    closing_price,
+                    mt.limit_offset
+                        .expect("limit offset is None but this is a limit order"),
+                ))
             } else {
                 // Using MARKET orders.
                 None
@@ -338,6 +1334,7 @@ fn process_market_data_thread(
     take_profit_percent: Option<f64>,
     confirmation_candles: Option<u8>,
     macd_trend_ma: Option<u16>,
+    price_source: candlestick::PriceSource,
 ) {
     info!(
         "starting {}ma compute thread for {:#?} using time frame {:#?} slow ma: {:#?}, fast ma {:#?}, signal: {:#?}",
@@ -354,20 +1351,17 @@ fn process_market_data_thread(
     let bex = Binance::new(ec);
     let am = AccountManager::new(ec_am, false, log_dir);
     let mut mt = MarketDataTracker {
-        slow_ma_data: ma::MAData::new(slow_ma.unwrap_or(0)),
-        fast_ma_data: ma::MAData::new(fast_ma.unwrap_or(0)),
-        macd: ma::MACD::new(),
-        desired_position: PositionType::None,
         candle_color_history: Vec::with_capacity(confirmation_candles.unwrap_or(0) as usize),
-        ema: ema,
         bvlt: bvlt,
-        trade_signal: signal,
         order_type: order_type,
         limit_offset: limit_offset,
         stop_percent: stop_percent,
         take_profit_percent: take_profit_percent,
         confirmation_candles: confirmation_candles,
         macd_trend_ma: ma::MAData::new(macd_trend_ma.unwrap_or(0)),
+        price_source,
+        symbol: Some(tp.symbol().to_string()),
+        ..MarketDataTracker::new(signal, slow_ma, fast_ma, ema)
     };
 
     let mut req_params: HashMap<&str, &str> = HashMap::with_capacity(3);
@@ -386,7 +1380,7 @@ fn process_market_data_thread(
         if let Ok(cd) = bex.get_cstick_data(&req_params) {
             let mut idx = 0;
             for stick in cd.iter() {
-                if let Ok(closing_price) = stick.close_price.parse::<f64>() {
+                if let Ok(candle) = stick.to_candle() {
                     if st >= stick.close_time {
                         // Candle stick is closed, we can use it for ma calculation.
                         prev_closing_price = if idx > 0 {
@@ -397,21 +1391,11 @@ fn process_market_data_thread(
 
                         idx += 1;
 
-                        process_close_data(
-                            &am,
-                            &tp,
-                            &mut mt,
-                            closing_price,
-                            prev_closing_price,
-                            false,
-                        );
-                        prev_closing_price = Some(closing_price);
+                        process_close_data(&am, &tp, &mut mt, &candle, prev_closing_price, false);
+                        prev_closing_price = Some(candle.close);
                     }
                 } else {
-                    error!(
-                        "failed to parse closing price {:?} to f64",
-                        stick.close_price
-                    );
+                    error!("failed to parse candle stick {:?} to f64", stick);
                 }
             }
         } else {
@@ -444,27 +1428,39 @@ fn process_market_data_thread(
                         if let Ok(cstick) = cstick {
                             let cstick_data: &serde_json::Value = &cstick["k"];
                             if cstick_data["x"] == false {
-                                // Not closed, keep reading waiting.
+                                // Not closed, alert on any provisional signal and
+                                // keep reading waiting.
+                                if let Some(candle) =
+                                    candlestick::Candle::from_kline_json(cstick_data)
+                                {
+                                    if let Some(ts) = mt.peek_signal(&tp, &candle) {
+                                        if ts.position != PositionType::None {
+                                            info!(
+                                                "{} provisional {:?} signal, close: {}",
+                                                tp.symbol(),
+                                                ts.position,
+                                                candle.close
+                                            );
+                                        }
+                                    }
+                                }
                                 continue;
                             }
 
-                            let closing_price = cstick_data["c"]
-                                .as_str()
-                                .unwrap_or("0.0")
-                                .parse::<f64>()
-                                .unwrap_or(-1.0);
-
-                            if closing_price > -1.0 {
-                                process_close_data(
-                                    &am,
-                                    &tp,
-                                    &mut mt,
-                                    closing_price,
-                                    prev_closing_price,
-                                    true,
-                                );
-                            } else {
-                                error!("failed to parse closing price: {}", cstick_data);
+                            match candlestick::Candle::from_kline_json(cstick_data) {
+                                Some(candle) => {
+                                    process_close_data(
+                                        &am,
+                                        &tp,
+                                        &mut mt,
+                                        &candle,
+                                        prev_closing_price,
+                                        true,
+                                    );
+                                }
+                                None => {
+                                    error!("failed to parse candle stick: {}", cstick_data);
+                                }
                             }
                         } else {
                             error!("failed to deserialize candlestick data: {}", s);
@@ -541,6 +1537,7 @@ fn md_bvlt_process_thread(
     limit_offset: Option<u8>,
     confirmation_candles: Option<u8>,
     macd_trend_ma: Option<u16>,
+    price_source: candlestick::PriceSource,
 ) {
     info!("starting {}ma bvlt thread for: {} using time frame: {}, slow ma: {:?}, fast ma: {:?}, split {}%, stop_pct: {:?}%",
         if ema { "e" } else { "s" }, symset, time_frame, slow_ma, fast_ma, split_pct, stop_percent);
@@ -580,6 +1577,7 @@ fn md_bvlt_process_thread(
                 take_profit_percent,
                 confirmation_candles,
                 macd_trend_ma,
+                price_source,
             );
         });
 
@@ -607,6 +1605,7 @@ fn md_process_thread(
     limit_offset: Option<u8>,
     confirmation_candles: Option<u8>,
     macd_trend_ma: Option<u16>,
+    price_source: candlestick::PriceSource,
 ) {
     info!("starting {}ma basic thread for: {} using time frame: {}, slow ma: {:?}, fast ma: {:?}, split: {}%, stop_percent: {:?}%",
         if ema { "e" } else { "s" }, symbol, time_frame, slow_ma, fast_ma, split_pct, stop_percent);
@@ -632,6 +1631,7 @@ fn md_process_thread(
             take_profit_percent,
             confirmation_candles,
             macd_trend_ma,
+            price_source,
         );
     });
 
@@ -771,6 +1771,8 @@ pub fn run_strategy(strat_cfg: &StrategyConfig, log_dir: &str, ec: &ExchangeConf
             TradeSignal::MaCross
         } else if signal.eq_ignore_ascii_case("macd") {
             TradeSignal::MACD
+        } else if signal.eq_ignore_ascii_case("stc") {
+            TradeSignal::Stc
         } else {
             panic!("Unsupported signal: {}", signal);
         }
@@ -813,6 +1815,21 @@ pub fn run_strategy(strat_cfg: &StrategyConfig, log_dir: &str, ec: &ExchangeConf
         None => None,
     };
 
+    // Price the signal indicators are computed from.
+    let price_source = match strat_cfg.members.get("PriceSource") {
+        Some(price_source) => match price_source.to_ascii_lowercase().as_str() {
+            "close" => candlestick::PriceSource::Close,
+            "hl2" => candlestick::PriceSource::Hl2,
+            "hlc3" => candlestick::PriceSource::Hlc3,
+            "ohlc4" => candlestick::PriceSource::Ohlc4,
+            _ => panic!(
+                "Unexpected PriceSource {:?}, use one of Close (default), HL2, HLC3 or OHLC4",
+                price_source
+            ),
+        },
+        None => candlestick::PriceSource::Close,
+    };
+
     // If have one set of symbols then we invest 100% in that, if we
     // have 2 sets of symbols then each gets 50% and so on....
     let asset_split_pct: u8 = (100 / pairs.len()) as u8;
@@ -852,6 +1869,7 @@ pub fn run_strategy(strat_cfg: &StrategyConfig, log_dir: &str, ec: &ExchangeConf
                     limit_range,
                     confirmation_candles,
                     macd_trend_ma,
+                    price_source,
                 );
             })
         } else {
@@ -872,6 +1890,7 @@ pub fn run_strategy(strat_cfg: &StrategyConfig, log_dir: &str, ec: &ExchangeConf
                     limit_range,
                     confirmation_candles,
                     macd_trend_ma,
+                    price_source,
                 );
             })
         };
@@ -891,6 +1910,798 @@ mod tests {
 
     use crate::utils;
 
+    fn candle(close: f64) -> candlestick::Candle {
+        candlestick::Candle {
+            open_time: 0,
+            close_time: 0,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+            symbol: None,
+        }
+    }
+
+    #[test]
+    fn feed_from_iter() {
+        let closes = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 5.0, 4.0, 3.0, 4.0];
+        let mut looped = MarketDataTracker::new(TradeSignal::MaCross, Some(5), Some(3), true);
+        for cp in closes.iter() {
+            looped.compute(*cp);
+        }
+
+        let mut fed = MarketDataTracker::new(TradeSignal::MaCross, Some(5), Some(3), true);
+        let candles: Vec<candlestick::Candle> = closes.iter().map(|cp| candle(*cp)).collect();
+        assert_eq!(fed.feed_from_iter(candles), closes.len());
+        assert_eq!(fed.slow_ma_data.latest(), looped.slow_ma_data.latest());
+        assert_eq!(fed.fast_ma_data.latest(), looped.fast_ma_data.latest());
+        assert!(fed.slow_ma_data.latest().is_some());
+    }
+
+    #[test]
+    fn update_order() {
+        let order = super::update_order(&[Indicator::StochRsi, Indicator::Signal, Indicator::Rsi]);
+        assert_eq!(
+            order,
+            vec![Indicator::Rsi, Indicator::StochRsi, Indicator::Signal]
+        );
+
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        let mut rsi = rsi::Rsi::new(DEFAULT_RSI_PERIOD);
+        let mut stoch_rsi = rsi::StochRsi::new(DEFAULT_RSI_PERIOD);
+        for i in 0..60 {
+            let cp = 100.0 + (i as f64 * 0.7).sin() * 5.0;
+            mt.on_candle(&candle(cp));
+
+            // StochRSI at each candle is based on that candle's RSI.
+            rsi.compute(cp);
+            if let Some(r) = rsi.latest() {
+                stoch_rsi.compute(r);
+            }
+            assert_eq!(mt.rsi.latest(), rsi.latest());
+            assert_eq!(mt.stoch_rsi.latest(), stoch_rsi.latest());
+        }
+        assert!(mt.stoch_rsi.latest().is_some());
+    }
+
+    #[test]
+    fn warmup() {
+        let history: Vec<candlestick::Candle> = (0..80)
+            .map(|i| {
+                let cp = 100.0 + i as f64 * 0.3 + (i as f64 * 0.5).sin() * 4.0;
+                candlestick::Candle {
+                    close_time: 60_000 * (i + 1) - 1,
+                    high: cp + 1.0,
+                    low: cp - 1.5,
+                    ..candle(cp)
+                }
+            })
+            .collect();
+
+        let mut warm = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        warm.warmup(&history);
+
+        let mut streamed = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        for c in history.iter() {
+            streamed.on_candle(c);
+        }
+
+        assert!(warm.macd.macd_latest.is_some());
+        assert!(warm.adx.latest().is_some());
+        assert_eq!(warm.macd.macd_latest, streamed.macd.macd_latest);
+        assert_eq!(warm.macd.macd_previous, streamed.macd.macd_previous);
+        assert_eq!(warm.macd.signal.latest(), streamed.macd.signal.latest());
+        assert_eq!(warm.adx.latest(), streamed.adx.latest());
+        assert_eq!(warm.rsi.latest(), streamed.rsi.latest());
+        assert_eq!(warm.stoch_rsi.latest(), streamed.stoch_rsi.latest());
+        assert_eq!(warm.candle_count, streamed.candle_count);
+        assert_eq!(warm.last_close, streamed.last_close);
+        assert_eq!(warm.last_close_time, Some(80 * 60_000 - 1));
+    }
+
+    #[test]
+    fn strategy_priority() {
+        let priority = StrategyPriority {
+            macd: 2,
+            ma_cross: 1,
+            ..StrategyPriority::default()
+        };
+        let signals = [
+            (TradeSignal::MaCross, PositionType::Short),
+            (TradeSignal::MACD, PositionType::Long),
+            (TradeSignal::Stc, PositionType::None),
+        ];
+        assert_eq!(priority.resolve(&signals), PositionType::Long);
+        assert_eq!(priority.resolve(&signals[..1]), PositionType::Short);
+        assert_eq!(priority.resolve(&signals[2..]), PositionType::None);
+
+        // Equal priorities which disagree cancel out.
+        let equal = StrategyPriority::default();
+        assert_eq!(equal.resolve(&signals), PositionType::None);
+        assert_eq!(
+            equal.resolve(&[
+                (TradeSignal::MACD, PositionType::Long),
+                (TradeSignal::Stc, PositionType::Long),
+            ]),
+            PositionType::Long
+        );
+    }
+
+    #[test]
+    fn strategy_mask() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let mask = StrategyMask::only(TradeSignal::MACD);
+
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        mt.strategy_mask = mask;
+        for cp in [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0].iter() {
+            mt.compute(*cp);
+            assert_eq!(mt.signal(&tp, *cp), PositionType::None);
+        }
+        assert!(mt.fast_ma_data.latest().is_none());
+
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        mt.strategy_mask = mask;
+        let closes = (0..40)
+            .map(|i| 100.0 - i as f64)
+            .chain((0..20).map(|i| 60.0 + i as f64));
+        let mut fired = false;
+        for cp in closes {
+            mt.compute(cp);
+            fired |= mt.signal(&tp, cp) == PositionType::Long;
+        }
+        assert!(fired);
+    }
+
+    #[test]
+    fn normalize() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let closes: Vec<f64> = (0..80)
+            .map(|i| 100.0 + i as f64 * 0.2 + 10.0 * (i as f64 * 0.4).sin())
+            .collect();
+
+        let signals = |normalize: NormalizeMode, scale: f64| -> Vec<PositionType> {
+            let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(8), Some(3), false);
+            mt.normalize = normalize;
+            closes
+                .iter()
+                .map(|cp| {
+                    mt.compute(cp * scale);
+                    mt.signal(&tp, cp * scale)
+                })
+                .collect()
+        };
+
+        // Price levels below the pair's decimal places hide the crosses.
+        let btc = signals(NormalizeMode::None, 1.0);
+        assert!(btc.iter().any(|p| *p != PositionType::None));
+        assert_ne!(btc, signals(NormalizeMode::None, 0.0001));
+
+        for mode in [NormalizeMode::PercentChange, NormalizeMode::ZScore].iter() {
+            let btc = signals(*mode, 1.0);
+            assert!(btc.iter().any(|p| *p != PositionType::None));
+            assert_eq!(btc, signals(*mode, 0.0001));
+        }
+    }
+
+    #[test]
+    fn symbol_mismatch() {
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(3), Some(2), false);
+        let eth = candlestick::Candle {
+            symbol: Some(String::from("ETHUSDT")),
+            ..candle(10.0)
+        };
+
+        // Unchecked until the tracker knows its symbol.
+        assert_eq!(mt.try_on_candle(&eth), Ok(()));
+
+        mt.symbol = Some(String::from("BTCUSDT"));
+        assert_eq!(
+            mt.try_on_candle(&eth),
+            Err(CtError::SymbolMismatch {
+                expected: String::from("BTCUSDT"),
+                actual: String::from("ETHUSDT"),
+            })
+        );
+        assert_eq!(mt.candle_count, 1);
+
+        let btc = candlestick::Candle {
+            symbol: Some(String::from("BTCUSDT")),
+            ..candle(11.0)
+        };
+        assert_eq!(mt.try_on_candle(&btc), Ok(()));
+        assert_eq!(mt.try_on_candle(&candle(12.0)), Ok(()));
+    }
+
+    #[test]
+    fn gap_policy() {
+        let candle_at = |i: u64, close: f64| candlestick::Candle {
+            open_time: i * 60_000,
+            close_time: i * 60_000 + 59_999,
+            ..candle(close)
+        };
+
+        for policy in [GapPolicy::Skip, GapPolicy::ForwardFill, GapPolicy::Error].iter() {
+            let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(3), Some(2), false);
+            mt.candle_interval = Some(Duration::from_secs(60));
+            mt.gap_policy = *policy;
+
+            assert_eq!(mt.try_on_candle(&candle_at(0, 10.0)), Ok(()));
+            assert_eq!(mt.try_on_candle(&candle_at(1, 11.0)), Ok(()));
+
+            // Two candles missing.
+            let result = mt.try_on_candle(&candle_at(4, 14.0));
+            match policy {
+                GapPolicy::Skip => {
+                    assert_eq!(result, Ok(()));
+                    assert_eq!(mt.candle_count, 3);
+                    assert!((mt.slow_ma_data.latest().unwrap() - 35.0 / 3.0).abs() < 1e-9);
+                }
+                GapPolicy::ForwardFill => {
+                    assert_eq!(result, Ok(()));
+                    assert_eq!(mt.candle_count, 5);
+                    assert!((mt.slow_ma_data.latest().unwrap() - 12.0).abs() < 1e-9);
+                }
+                GapPolicy::Error => {
+                    assert_eq!(result, Err(CtError::CandleGap { missing: 2 }));
+                    assert_eq!(mt.candle_count, 2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn price_source() {
+        let candles: Vec<candlestick::Candle> = (0..10)
+            .map(|i| {
+                let cp = 100.0 + i as f64;
+                candlestick::Candle {
+                    high: cp + 3.0,
+                    low: cp - 1.0,
+                    ..candle(cp)
+                }
+            })
+            .collect();
+
+        let mut close = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        let mut hlc3 = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        hlc3.price_source = candlestick::PriceSource::Hlc3;
+        close.warmup(&candles);
+        hlc3.warmup(&candles);
+
+        // The typical price sits 2/3 above each close.
+        let close_ma = close.slow_ma_data.latest().unwrap();
+        let hlc3_ma = hlc3.slow_ma_data.latest().unwrap();
+        assert!((hlc3_ma - close_ma - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(hlc3.last_close, Some(109.0));
+    }
+
+    #[test]
+    fn all_ready() {
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(30), Some(10), false);
+        for i in 0..40 {
+            let cp = 100.0 + (i as f64 * 0.9).sin() * 3.0;
+            mt.on_candle(&candlestick::Candle {
+                high: cp + 1.0,
+                low: cp - 1.0,
+                ..candle(cp)
+            });
+
+            // The slow MA is the last to get a value, on candle 30.
+            assert_eq!(mt.all_ready(), i >= 29);
+            assert_eq!(mt.all_ready(), mt.slow_ma_data.latest().is_some());
+        }
+        assert!(mt.strategy_ready(TradeSignal::MaCross));
+        assert!(!mt.strategy_ready(TradeSignal::MACD));
+    }
+
+    #[test]
+    fn loss_streak_guard() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let closes = [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0];
+        let run = |mt: &mut MarketDataTracker| -> Vec<PositionType> {
+            closes
+                .iter()
+                .map(|cp| {
+                    mt.compute(*cp);
+                    mt.signal(&tp, *cp)
+                })
+                .collect()
+        };
+
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        mt.loss_streak_guard = Some(position::LossStreakGuard::new(3));
+        for _ in 0..3 {
+            mt.record_outcome(-10.0);
+        }
+        assert!(run(&mut mt).iter().all(|p| *p == PositionType::None));
+        assert_eq!(mt.desired_position, PositionType::None);
+
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        mt.loss_streak_guard = Some(position::LossStreakGuard::new(3));
+        for _ in 0..3 {
+            mt.record_outcome(-10.0);
+        }
+        mt.record_outcome(5.0);
+        assert!(run(&mut mt).contains(&PositionType::Long));
+    }
+
+    #[test]
+    fn signal_timing() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        for cp in [13.0, 12.0, 11.0, 10.0, 9.0, 8.0].iter() {
+            mt.on_candle(&candle(*cp));
+            mt.signal(&tp, *cp);
+        }
+        assert_eq!(mt.peek_signal(&tp, &candle(13.0)), None);
+
+        // Intrabar the forming candle crosses the fast MA up through the slow.
+        mt.signal_timing = SignalTiming::Intrabar;
+        assert_eq!(
+            mt.peek_signal(&tp, &candle(13.0)),
+            Some(TimedSignal {
+                position: PositionType::Long,
+                provisional: true,
+            })
+        );
+
+        // It then closes back below the cross level.
+        mt.on_candle(&candle(8.5));
+        assert_eq!(mt.signal(&tp, 8.5), PositionType::None);
+        assert_eq!(mt.desired_position, PositionType::None);
+    }
+
+    #[test]
+    fn rsi_band_signal() {
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        mt.rsi_long_band = Some(Hysteresis::new(30.0, 25.0));
+        mt.rsi_short_band = Some(Hysteresis::new(70.0, 75.0));
+
+        // A selloff takes the RSI to 0, then it flickers either side of 30.
+        let mut closes: Vec<f64> = (0..=20).map(|i| 1000.0 - i as f64).collect();
+        for _ in 0..40 {
+            let cp = *closes.last().unwrap();
+            closes.push(cp + 4.0);
+            closes.push(cp + 4.0 - 9.5);
+        }
+
+        let mut prev_rsi = None;
+        let mut crosses = 0;
+        let mut longs = 0;
+        for cp in closes.iter() {
+            mt.on_candle(&candle(*cp));
+            let rsi = mt.rsi.latest();
+            if prev_rsi.is_some_and(|prev| prev <= 30.0) && rsi.is_some_and(|rsi| rsi > 30.0) {
+                crosses += 1;
+            }
+            prev_rsi = rsi;
+
+            assert_ne!(mt.rsi_band_signal(), PositionType::Short);
+            if mt.rsi_band_signal() == PositionType::Long {
+                longs += 1;
+            }
+        }
+        assert!(crosses > 10);
+        assert_eq!(longs, 1);
+    }
+
+    #[test]
+    fn rsi_percentile() {
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        mt.update_order = super::update_order(&[Indicator::Rsi]);
+        mt.rsi_percentile = Some(percentile::PercentRank::new(50));
+
+        let mut cp = 100.0;
+        for i in 0..40 {
+            cp += if i % 2 == 0 { 1.0 } else { -1.0 };
+            mt.on_candle(&candle(cp));
+        }
+        for _ in 0..3 {
+            cp += 5.0;
+            mt.on_candle(&candle(cp));
+        }
+
+        let rank = mt.rsi_percentile.as_ref().unwrap().latest();
+        assert_eq!(rank, Some(100.0));
+    }
+
+    #[test]
+    fn circuit_breaker() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let closes = [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0];
+
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        mt.circuit_breaker = Some(position::CircuitBreaker::new(5.0));
+        mt.desired_position = PositionType::Short;
+        for equity in [1000.0, 980.0, 960.0, 940.0].iter() {
+            mt.record_equity(*equity);
+        }
+        assert_eq!(mt.desired_position, PositionType::None);
+
+        let signals: Vec<PositionType> = closes
+            .iter()
+            .map(|cp| {
+                mt.compute(*cp);
+                mt.signal(&tp, *cp)
+            })
+            .collect();
+        assert!(signals.iter().all(|s| *s == PositionType::None));
+
+        // Entries resume once reset.
+        mt.circuit_breaker.as_mut().unwrap().reset();
+        mt.compute(8.0);
+        mt.compute(14.0);
+        assert_eq!(mt.signal(&tp, 14.0), PositionType::Long);
+    }
+
+    #[test]
+    fn signal_event_display() {
+        let event = SignalEvent {
+            symbol: String::from("BTCUSDT"),
+            kind: TradeSignal::MaCross,
+            direction: PositionType::Long,
+            price: 101.5,
+        };
+        assert_eq!(event.to_string(), "BTCUSDT MaCross LONG @ 101.5");
+        assert_eq!(event.to_string().parse::<SignalEvent>(), Ok(event));
+
+        let event = SignalEvent {
+            symbol: String::from("ETHBTC"),
+            kind: TradeSignal::MACD,
+            direction: PositionType::Short,
+            price: 0.0625,
+        };
+        assert_eq!(event.to_string().parse::<SignalEvent>(), Ok(event));
+
+        assert!("BTCUSDT MaCross UP @ 1".parse::<SignalEvent>().is_err());
+        assert!("BTCUSDT MaCross LONG".parse::<SignalEvent>().is_err());
+    }
+
+    #[test]
+    fn deferred_entry() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let run = |mode: DeferredEntry, next: f64| -> (Vec<PositionType>, PositionType) {
+            let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+            mt.deferred_entry = Some(mode);
+            let mut signals = vec![];
+            for cp in [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0, next].iter() {
+                mt.on_candle(&candlestick::Candle {
+                    high: cp + 0.5,
+                    low: cp - 0.5,
+                    ..candle(*cp)
+                });
+                signals.push(mt.signal(&tp, *cp));
+            }
+            (signals, mt.desired_position)
+        };
+
+        // The cross on the 13.0 candle is held back, then the next candle closes
+        // back down and fails to confirm it.
+        let (signals, desired) = run(DeferredEntry::Close, 12.5);
+        assert!(signals.iter().all(|s| *s == PositionType::None));
+        assert_eq!(desired, PositionType::None);
+
+        let (signals, desired) = run(DeferredEntry::Close, 13.2);
+        assert_eq!(signals[6], PositionType::None);
+        assert_eq!(signals[7], PositionType::Long);
+        assert_eq!(desired, PositionType::Long);
+
+        // Higher close but not above the signal candle's high.
+        let (signals, _) = run(DeferredEntry::Breakout, 13.2);
+        assert_eq!(signals[7], PositionType::None);
+        let (signals, _) = run(DeferredEntry::Breakout, 13.6);
+        assert_eq!(signals[7], PositionType::Long);
+    }
+
+    #[test]
+    fn min_bars_between_entries() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, Some(4), Some(2), false);
+        mt.min_bars_between_entries = Some(5);
+        for cp in [13.0, 12.0, 11.0, 10.0, 9.0, 8.0].iter() {
+            mt.slow_ma_data.compute(*cp, false);
+            mt.fast_ma_data.compute(*cp, false);
+        }
+
+        // Flat then rising until the MACD goes long.
+        let mut cp = 8.0;
+        for _ in 0..40 {
+            mt.compute(cp);
+            assert_eq!(mt.signal(&tp, cp), PositionType::None);
+        }
+        loop {
+            cp += 0.5;
+            mt.compute(cp);
+            if mt.signal(&tp, cp) == PositionType::Long {
+                break;
+            }
+        }
+        assert_eq!(mt.last_entry_bar, Some(mt.candle_count));
+
+        mt.compute(cp);
+        mt.signal(&tp, cp);
+
+        // The MA cross fires two candles later, too soon after the MACD entry.
+        mt.trade_signal = TradeSignal::MaCross;
+        let mut unguarded = mt.clone();
+        unguarded.min_bars_between_entries = None;
+        unguarded.compute(13.0);
+        assert_eq!(unguarded.signal(&tp, 13.0), PositionType::Long);
+
+        mt.compute(13.0);
+        assert_eq!(mt.signal(&tp, 13.0), PositionType::None);
+    }
+
+    #[test]
+    fn repeated_signal_keeps_entry_window() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let mut mt = MarketDataTracker::new(TradeSignal::MaTrendReversal, None, Some(1), false);
+        mt.min_bars_between_entries = Some(3);
+
+        // Long at the trough, Long again after the dip and Short at the peak.
+        let closes = [100.0, 98.0, 96.0, 98.0, 98.0, 96.0, 98.0, 100.0, 98.0];
+        let signals: Vec<PositionType> = closes
+            .iter()
+            .map(|cp| {
+                mt.compute(*cp);
+                mt.signal(&tp, *cp)
+            })
+            .collect();
+
+        assert_eq!(signals[3], PositionType::Long);
+        assert_eq!(signals[6], PositionType::Long);
+        assert_eq!(mt.last_long_index, Some(4));
+        // Two candles after the repeated Long, but five after the entry.
+        assert_eq!(signals[8], PositionType::Short);
+        assert_eq!(mt.last_entry_bar, Some(9));
+    }
+
+    #[test]
+    fn signal_sink() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        mt.set_signal_sink(sender);
+
+        let mut cp = 100.0;
+        for _ in 0..40 {
+            mt.compute(cp);
+            assert_eq!(mt.signal(&tp, cp), PositionType::None);
+        }
+        loop {
+            cp += 0.5;
+            mt.compute(cp);
+            if mt.signal(&tp, cp) != PositionType::None {
+                break;
+            }
+        }
+
+        let events: Vec<SignalEvent> = receiver.try_iter().collect();
+        assert_eq!(
+            events,
+            vec![SignalEvent {
+                symbol: tp.symbol().to_string(),
+                kind: TradeSignal::MACD,
+                direction: PositionType::Long,
+                price: cp,
+            }]
+        );
+    }
+
+    #[test]
+    fn higher_timeframe_filter() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let trend = |closes: &[f64]| {
+            let mut higher = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+            for cp in closes.iter() {
+                higher.compute(*cp);
+            }
+            let mut filter = HigherTimeframeFilter::new();
+            filter.update(&higher);
+            filter
+        };
+        let last_signal = |filter: HigherTimeframeFilter| {
+            let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+            mt.higher_timeframe = Some(filter);
+            let mut last = PositionType::None;
+            for cp in [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0].iter() {
+                mt.compute(*cp);
+                last = mt.signal(&tp, *cp);
+            }
+            last
+        };
+
+        let down = trend(&[20.0, 19.0, 18.0, 17.0, 16.0]);
+        assert_eq!(down.trend, PositionType::Short);
+        assert_eq!(last_signal(down), PositionType::None);
+
+        let up = trend(&[16.0, 17.0, 18.0, 19.0, 20.0]);
+        assert_eq!(up.trend, PositionType::Long);
+        assert_eq!(last_signal(up), PositionType::Long);
+
+        // Unknown until the higher timeframe's MAs have values.
+        assert!(!trend(&[]).permits(PositionType::Long));
+    }
+
+    #[test]
+    fn min_volume() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let closes: Vec<f64> = (0..80)
+            .map(|i| {
+                if i < 40 {
+                    100.0
+                } else {
+                    100.0 + (i - 39) as f64 * 0.5
+                }
+            })
+            .collect();
+        // Signals with every candle trading 100 except the one at thin_index.
+        let signals = |min_volume: Option<f64>, thin_index: usize| {
+            let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+            mt.min_volume = min_volume;
+            closes
+                .iter()
+                .enumerate()
+                .map(|(i, cp)| {
+                    let mut c = candle(*cp);
+                    c.volume = if i == thin_index { 10.0 } else { 100.0 };
+                    mt.on_candle(&c);
+                    mt.signal(&tp, *cp)
+                })
+                .collect::<Vec<PositionType>>()
+        };
+
+        let unfiltered = signals(None, closes.len());
+        let cross = unfiltered
+            .iter()
+            .position(|s| *s == PositionType::Long)
+            .expect("no MACD cross");
+
+        assert_eq!(signals(Some(50.0), closes.len())[cross], PositionType::Long);
+        assert_eq!(signals(Some(50.0), cross)[cross], PositionType::None);
+
+        // No candle, no known volume.
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        mt.min_volume = Some(50.0);
+        let mut last = PositionType::None;
+        for cp in closes[..=cross].iter() {
+            mt.compute(*cp);
+            last = mt.signal(&tp, *cp);
+        }
+        assert_eq!(last, PositionType::None);
+
+        // Closes computed after candles don't take on the last candle's volume.
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        mt.min_volume = Some(50.0);
+        let mut c = candle(closes[0]);
+        c.volume = 100.0;
+        mt.on_candle(&c);
+        let mut last = PositionType::None;
+        for cp in closes[1..=cross].iter() {
+            mt.compute(*cp);
+            last = mt.signal(&tp, *cp);
+        }
+        assert_eq!(last, PositionType::None);
+    }
+
+    #[test]
+    fn metrics() {
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        for cp in [13.0, 12.0, 11.0, 10.0].iter() {
+            mt.on_candle(&candle(*cp));
+        }
+
+        let metrics = mt.metrics();
+        assert_eq!(metrics.get("ma.fast"), Some(&10.5));
+        assert_eq!(metrics.get("ma.slow"), Some(&11.5));
+        assert_eq!(metrics.get("close"), Some(&10.0));
+        assert_eq!(metrics.len(), 3);
+        for key in ["macd.histogram", "adx", "rsi", "stoch_rsi"].iter() {
+            assert!(!metrics.contains_key(*key));
+        }
+    }
+
+    #[test]
+    fn contrarian() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let closes: Vec<f64> = (0..60)
+            .map(|i| {
+                if i < 40 {
+                    100.0 - i as f64 * 0.5
+                } else {
+                    80.0 + (i - 40) as f64
+                }
+            })
+            .collect();
+        let run = |contrarian: bool| -> Vec<PositionType> {
+            let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+            mt.contrarian = contrarian;
+            closes
+                .iter()
+                .map(|cp| {
+                    mt.compute(*cp);
+                    mt.signal(&tp, *cp)
+                })
+                .collect()
+        };
+
+        let plain = run(false);
+        let inverted = run(true);
+        assert!(plain.contains(&PositionType::Long));
+        for (p, i) in plain.iter().zip(inverted.iter()) {
+            match p {
+                PositionType::Long => assert_eq!(*i, PositionType::Short),
+                PositionType::Short => assert_eq!(*i, PositionType::Long),
+                PositionType::None => assert_eq!(*i, PositionType::None),
+            }
+        }
+    }
+
+    #[test]
+    fn candles_since() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        for cp in [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0].iter() {
+            mt.compute(*cp);
+            mt.signal(&tp, *cp);
+        }
+
+        assert_eq!(mt.desired_position, PositionType::Long);
+        assert_eq!(mt.candles_since(PositionType::Long), Some(0));
+        assert_eq!(mt.candles_since(PositionType::Short), None);
+
+        for cp in [14.0, 15.0, 16.0, 17.0, 18.0].iter() {
+            mt.compute(*cp);
+            mt.signal(&tp, *cp);
+        }
+
+        assert_eq!(mt.candles_since(PositionType::Long), Some(5));
+    }
+
+    #[test]
+    fn reset_signals() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(4), Some(2), false);
+        mt.min_bars_between_entries = Some(100);
+        let closes = [13.0, 12.0, 11.0, 10.0, 9.0, 8.0, 13.0];
+        let signals: Vec<PositionType> = closes
+            .iter()
+            .map(|cp| {
+                mt.compute(*cp);
+                mt.signal(&tp, *cp)
+            })
+            .collect();
+        assert_eq!(signals.last(), Some(&PositionType::Long));
+
+        // The same cross is held back by the entry cooldown until reset.
+        let mut not_reset = mt.clone();
+        assert_eq!(not_reset.signal(&tp, 13.0), PositionType::None);
+
+        mt.reset_signals();
+        assert_eq!(mt.desired_position, PositionType::None);
+        assert_eq!(mt.candles_since(PositionType::Long), None);
+        assert_eq!(mt.last_entry_bar, None);
+        assert!(mt.slow_ma_data.latest().is_some());
+
+        assert_eq!(mt.signal(&tp, 13.0), PositionType::Long);
+        assert_eq!(mt.desired_position, PositionType::Long);
+    }
+
+    #[test]
+    fn data_is_fresh() {
+        let now = Utc::now();
+        let mut mt = MarketDataTracker::new(TradeSignal::MACD, None, None, true);
+        assert!(!mt.data_is_fresh(now, Duration::from_secs(5 * 60)));
+
+        let mut stale = candle(1.0);
+        stale.close_time = (now.timestamp_millis() - 10 * 60 * 1000) as u64;
+        mt.on_candle(&stale);
+        assert!(!mt.data_is_fresh(now, Duration::from_secs(5 * 60)));
+        assert!(mt.data_is_fresh(now, Duration::from_secs(15 * 60)));
+    }
+
     #[test]
     fn ws_market_data_stream() {
         utils::init_logging("testlogs/ma/ws_market_data_stream", "info");
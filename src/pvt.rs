@@ -0,0 +1,63 @@
+// Price volume trend (pvt.rs), a running total of the volume weighted by the
+// percentage change of the close.
+// https://www.investopedia.com/terms/v/vptindicator.asp
+use crate::prevclose;
+
+use prevclose::PrevClose;
+
+#[derive(Debug, Default)]
+pub struct Pvt {
+    latest: Option<f64>,
+    prev_close: PrevClose,
+}
+
+impl Pvt {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Pvt::default()
+    }
+
+    // Current running total, None until there's a previous close to compare with.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Add the candle's volume, weighted by the change in close since the previous
+    // candle.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64, volume: f64) {
+        if !close_price.is_finite() || !volume.is_finite() {
+            return;
+        }
+
+        let prev_close = self.prev_close.prev();
+        match (self.prev_close.update(close_price), prev_close) {
+            (Some(change), Some(prev_close)) if prev_close != 0.0 => {
+                let change = (change / prev_close) * volume;
+                self.latest = Some(self.latest.unwrap_or(0.0) + change);
+            }
+            // First candle, or a zero close we can't take a percentage of.
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pvt() {
+        let mut pvt = Pvt::new();
+        pvt.compute(100.0, 500.0);
+        assert!(pvt.latest().is_none());
+
+        pvt.compute(110.0, 1000.0);
+        assert!((pvt.latest().unwrap() - 100.0).abs() < 1e-9);
+
+        pvt.compute(0.0, 1000.0);
+        pvt.compute(50.0, 1000.0);
+        assert!((pvt.latest().unwrap() - 100.0 + 1000.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,152 @@
+// Market regime classification (regime.rs), lets strategies choose between trend
+// following and mean reversion.
+use crate::ma;
+use crate::process_md;
+
+// ADX readings at or above this are considered to be trending.
+static ADX_TREND_THRESHOLD: f64 = 25.0;
+
+// Percentage MA slope per candle, and percentage separation of the fast and slow
+// MAs, at which each reads as full strength.
+static SLOPE_FULL_SCALE: f64 = 0.5;
+static SEPARATION_FULL_SCALE: f64 = 1.5;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MarketRegime {
+    TrendingUp,
+    TrendingDown,
+    Ranging,
+    Unknown,
+}
+
+// The MA trends are judged by, the slowest configured.
+fn trend_ma(mt: &process_md::MarketDataTracker) -> &ma::MAData {
+    if mt.slow_ma_data.num_candles > 0 {
+        &mt.slow_ma_data
+    } else {
+        &mt.fast_ma_data
+    }
+}
+
+// Classify the market using ADX for trend strength and the slope of the slowest
+// configured MA for trend direction.
+#[allow(dead_code)]
+pub fn classify_regime(mt: &process_md::MarketDataTracker) -> MarketRegime {
+    let (adx, slope) = match (mt.adx.latest(), trend_ma(mt).slope()) {
+        (Some(adx), Some(slope)) => (adx, slope),
+        _ => return MarketRegime::Unknown,
+    };
+
+    if adx < ADX_TREND_THRESHOLD {
+        MarketRegime::Ranging
+    } else if slope > 0.0 {
+        MarketRegime::TrendingUp
+    } else if slope < 0.0 {
+        MarketRegime::TrendingDown
+    } else {
+        MarketRegime::Ranging
+    }
+}
+
+// Trend strength from 0.0 (flat) to 1.0 (steep), regardless of direction, for
+// display. The average of the trend MA's slope and, when both MAs are configured,
+// the separation of the fast and slow MAs, each relative to the price.
+#[allow(dead_code)]
+pub fn trend_strength(mt: &process_md::MarketDataTracker) -> f64 {
+    let trend_ma = trend_ma(mt);
+    let (latest, slope) = match (trend_ma.latest(), trend_ma.slope()) {
+        (Some(latest), Some(slope)) if latest != 0.0 => (latest, slope),
+        _ => return 0.0,
+    };
+
+    let slope_strength = (100.0 * slope.abs() / latest / SLOPE_FULL_SCALE).min(1.0);
+
+    if mt.slow_ma_data.num_candles == 0 || mt.fast_ma_data.num_candles == 0 {
+        return slope_strength;
+    }
+
+    match (mt.fast_ma_data.latest(), mt.slow_ma_data.latest()) {
+        (Some(fast), Some(slow)) => {
+            let separation = 100.0 * (fast - slow).abs() / slow.abs();
+            let separation_strength = (separation / SEPARATION_FULL_SCALE).min(1.0);
+            (slope_strength + separation_strength) / 2.0
+        }
+        _ => slope_strength,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::candlestick::Candle;
+    use process_md::{MarketDataTracker, TradeSignal};
+
+    fn candle(close: f64) -> Candle {
+        Candle {
+            open_time: 0,
+            close_time: 0,
+            open: close,
+            high: close + 0.5,
+            low: close - 0.5,
+            close,
+            volume: 0.0,
+            symbol: None,
+        }
+    }
+
+    fn tracker(closes: Vec<f64>) -> MarketDataTracker {
+        let mut mt = MarketDataTracker::new(TradeSignal::MaCross, Some(10), Some(5), false);
+        mt.feed_from_iter(closes.into_iter().map(candle));
+        mt
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(classify_regime(&tracker(vec![])), MarketRegime::Unknown);
+        assert_eq!(
+            classify_regime(&tracker(vec![1.0, 2.0, 3.0])),
+            MarketRegime::Unknown
+        );
+    }
+
+    #[test]
+    fn trending_up() {
+        let closes = (0..60).map(|i| 100.0 + i as f64).collect();
+        assert_eq!(classify_regime(&tracker(closes)), MarketRegime::TrendingUp);
+    }
+
+    #[test]
+    fn trending_down() {
+        let closes = (0..60).map(|i| 100.0 - i as f64).collect();
+        assert_eq!(
+            classify_regime(&tracker(closes)),
+            MarketRegime::TrendingDown
+        );
+    }
+
+    #[test]
+    fn ranging() {
+        let closes = (0..60)
+            .map(|i| if i % 2 == 0 { 100.0 } else { 101.0 })
+            .collect();
+        assert_eq!(classify_regime(&tracker(closes)), MarketRegime::Ranging);
+    }
+
+    #[test]
+    fn trend_strength() {
+        let steep = (0..60).map(|i| 100.0 * 1.03f64.powi(i)).collect();
+        assert!(super::trend_strength(&tracker(steep)) > 0.95);
+
+        let flat = (0..60).map(|_| 100.0).collect();
+        assert_eq!(super::trend_strength(&tracker(flat)), 0.0);
+
+        // Mirrored trends around the same price level.
+        let up =
+            super::trend_strength(&tracker((0..60).map(|i| 100.0 + 0.05 * i as f64).collect()));
+        let down =
+            super::trend_strength(&tracker((0..60).map(|i| 103.0 - 0.05 * i as f64).collect()));
+        assert!(up > 0.05 && up < 0.95);
+        assert!((up - down).abs() < 0.05 * up);
+    }
+}
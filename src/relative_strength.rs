@@ -0,0 +1,125 @@
+// Relative strength (relative_strength.rs), the ratio of one pair's price to
+// another's, rising while the first outperforms the second.
+use crate::process_md;
+
+use process_md::MarketDataTracker;
+
+// Price ratio a / b normalised to 1.0 at the first pair of prices, one value for
+// each pair of prices. Where b is zero the previous value is repeated. Empty if
+// either first price is zero.
+#[allow(dead_code)]
+pub fn relative_strength(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut rs = Vec::with_capacity(a.len().min(b.len()));
+    let base = match (a.first(), b.first()) {
+        (Some(a0), Some(b0)) if *a0 != 0.0 && *b0 != 0.0 => a0 / b0,
+        _ => return rs,
+    };
+
+    let mut prev = 1.0;
+    for (pa, pb) in a.iter().zip(b.iter()) {
+        if *pb != 0.0 {
+            prev = (pa / pb) / base;
+        }
+        rs.push(prev);
+    }
+
+    rs
+}
+
+// Relative strength of two trackers' pairs, updated as both advance.
+#[derive(Debug, Default)]
+pub struct RelativeStrengthStream {
+    base: Option<f64>,
+    latest: Option<f64>,
+    // Close time of the candles the latest value was computed from.
+    last_close_time: Option<u64>,
+}
+
+impl RelativeStrengthStream {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        RelativeStrengthStream::default()
+    }
+
+    // Current relative strength, 1.0 at the first update.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Update from the trackers' last candles. Only computed once both have
+    // processed the same candle, and once per candle.
+    #[allow(dead_code)]
+    pub fn update(&mut self, a: &MarketDataTracker, b: &MarketDataTracker) -> Option<f64> {
+        if a.last_close_time != b.last_close_time || a.last_close_time == self.last_close_time {
+            return self.latest;
+        }
+
+        if let (Some(pa), Some(pb)) = (a.last_close, b.last_close) {
+            if pa != 0.0 && pb != 0.0 {
+                let ratio = pa / pb;
+                let base = *self.base.get_or_insert(ratio);
+                self.latest = Some(ratio / base);
+                self.last_close_time = a.last_close_time;
+            }
+        }
+
+        self.latest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candle;
+    use process_md::TradeSignal;
+
+    fn candle(close_time: u64, close: f64) -> Candle {
+        Candle {
+            open_time: close_time - 59_999,
+            close_time,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+            symbol: None,
+        }
+    }
+
+    #[test]
+    fn outperforming() {
+        let a: Vec<f64> = (0..10).map(|i| 100.0 * 1.02f64.powi(i)).collect();
+        let b: Vec<f64> = (0..10).map(|i| 50.0 * 1.01f64.powi(i)).collect();
+
+        let rs = relative_strength(&a, &b);
+        assert_eq!(rs.len(), 10);
+        assert_eq!(rs[0], 1.0);
+        assert!(rs.windows(2).all(|w| w[1] > w[0]));
+    }
+
+    #[test]
+    fn stream() {
+        let mut mt_a = MarketDataTracker::new(TradeSignal::MaCross, Some(3), Some(2), false);
+        let mut mt_b = MarketDataTracker::new(TradeSignal::MaCross, Some(3), Some(2), false);
+        let mut stream = RelativeStrengthStream::new();
+
+        let mut prev = 0.0;
+        for i in 0..10 {
+            let close_time = 60_000 * (i + 1) - 1;
+            mt_a.on_candle(&candle(close_time, 100.0 * 1.02f64.powi(i as i32)));
+
+            // Not updated until both trackers have the candle.
+            if i > 0 {
+                assert_eq!(stream.update(&mt_a, &mt_b), Some(prev));
+            }
+
+            mt_b.on_candle(&candle(close_time, 50.0 * 1.01f64.powi(i as i32)));
+            let rs = stream.update(&mt_a, &mt_b).unwrap();
+            assert!(rs > prev);
+            prev = rs;
+        }
+
+        assert_eq!(stream.update(&mt_a, &mt_b), Some(prev));
+    }
+}
@@ -0,0 +1,63 @@
+// Rate of change (roc.rs), percentage change of the close over a number of candles.
+use crate::window;
+
+use window::RollingWindow;
+
+#[derive(Debug, Clone)]
+pub struct Roc {
+    latest: Option<f64>,
+
+    // Close prices, newest at the front. Holds period + 1 values.
+    acc: RollingWindow<f64>,
+    // Number of candles to look back over.
+    pub period: u16,
+}
+
+impl Roc {
+    #[allow(dead_code)]
+    pub fn new(period: u16) -> Self {
+        Roc {
+            latest: None,
+            acc: RollingWindow::new(period as usize + 1),
+            period,
+        }
+    }
+
+    // Current rate of change, as a percentage.
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Compute the latest rate of change based on the close price.
+    pub fn compute(&mut self, close_price: f64) {
+        if self.period == 0 || !close_price.is_finite() {
+            return;
+        }
+
+        self.acc.push(close_price);
+        if self.acc.is_full() {
+            let oldest = self.acc.oldest().unwrap();
+            self.latest = if oldest != 0.0 {
+                Some(100.0 * (close_price - oldest) / oldest)
+            } else {
+                None
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roc() {
+        let mut roc = Roc::new(2);
+        roc.compute(100.0);
+        roc.compute(105.0);
+        assert!(roc.latest().is_none());
+
+        roc.compute(110.0);
+        assert_eq!(roc.latest(), Some(10.0));
+    }
+}
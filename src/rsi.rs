@@ -0,0 +1,265 @@
+// Relative strength index (rsi.rs), average gains against average losses as a
+// 0..100 oscillator. Also the stochastic RSI, RSI's position within its range.
+// https://www.investopedia.com/terms/r/rsi.asp
+use crate::ma;
+use crate::prevclose;
+use crate::window;
+
+use ma::MAData;
+use prevclose::PrevClose;
+use window::RollingWindow;
+
+// How gains and losses are averaged.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RsiSmoothing {
+    // Wilder's smoothed moving average, as on most charting platforms.
+    Wilder,
+    // Cutler's simple moving average, which doesn't depend on the starting point.
+    Cutler,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    // Number of candles averaged over.
+    pub period: u16,
+    pub smoothing: RsiSmoothing,
+
+    prev_close: PrevClose,
+    avg_gain: f64,
+    avg_loss: f64,
+    // Changes collected towards the first average.
+    num_changes: u16,
+    // Gains and losses over the period, for Cutler's RSI.
+    gains: RollingWindow<f64>,
+    losses: RollingWindow<f64>,
+
+    latest: Option<f64>,
+    // EMA of the RSI values, when smoothed.
+    smoothed: Option<MAData>,
+}
+
+impl Rsi {
+    pub fn new(period: u16) -> Self {
+        Rsi::new_with_smoothing(period, RsiSmoothing::Wilder)
+    }
+
+    pub fn new_with_smoothing(period: u16, smoothing: RsiSmoothing) -> Self {
+        let window = if smoothing == RsiSmoothing::Cutler {
+            period as usize
+        } else {
+            0
+        };
+
+        Rsi {
+            period,
+            smoothing,
+            prev_close: PrevClose::default(),
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+            num_changes: 0,
+            gains: RollingWindow::new(window),
+            losses: RollingWindow::new(window),
+            latest: None,
+            smoothed: None,
+        }
+    }
+
+    // Also keep an EMA over 'period' of the RSI values, see smoothed_latest().
+    #[allow(dead_code)]
+    pub fn smoothed(mut self, period: u16) -> Self {
+        self.smoothed = Some(MAData::new(period));
+        self
+    }
+
+    // Current RSI value.
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Current EMA smoothed RSI value, None unless constructed smoothed().
+    #[allow(dead_code)]
+    pub fn smoothed_latest(&self) -> Option<f64> {
+        self.smoothed.as_ref()?.latest()
+    }
+
+    // Compute the latest RSI value based on the close price.
+    pub fn compute(&mut self, close_price: f64) {
+        if self.period == 0 || !close_price.is_finite() {
+            return;
+        }
+
+        if let Some(change) = self.prev_close.update(close_price) {
+            let gain = change.max(0.0);
+            let loss = (-change).max(0.0);
+            let period = self.period as f64;
+
+            if self.smoothing == RsiSmoothing::Cutler {
+                self.gains.push(gain);
+                self.losses.push(loss);
+                if self.gains.is_full() {
+                    self.latest = Some(rsi(self.gains.sum() / period, self.losses.sum() / period));
+                }
+            } else if self.num_changes < self.period {
+                // The first averages are plain averages.
+                self.avg_gain += gain / period;
+                self.avg_loss += loss / period;
+                self.num_changes += 1;
+            } else {
+                self.avg_gain = ((self.avg_gain * (period - 1.0)) + gain) / period;
+                self.avg_loss = ((self.avg_loss * (period - 1.0)) + loss) / period;
+            }
+
+            if self.smoothing == RsiSmoothing::Wilder && self.num_changes == self.period {
+                self.latest = Some(rsi(self.avg_gain, self.avg_loss));
+            }
+
+            if let (Some(smoothed), Some(latest)) = (self.smoothed.as_mut(), self.latest) {
+                smoothed.compute(latest, true);
+            }
+        }
+    }
+}
+
+// RSI from the average gain and loss, 50 when there's been no movement.
+fn rsi(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss > 0.0 {
+        100.0 - (100.0 / (1.0 + (avg_gain / avg_loss)))
+    } else if avg_gain > 0.0 {
+        100.0
+    } else {
+        50.0
+    }
+}
+
+// Stochastic RSI, where the RSI sits within its range over the period, 0..100.
+#[derive(Debug, Clone)]
+pub struct StochRsi {
+    rsi_window: RollingWindow<f64>,
+    latest: Option<f64>,
+}
+
+impl StochRsi {
+    pub fn new(period: u16) -> Self {
+        StochRsi {
+            rsi_window: RollingWindow::new(period as usize),
+            latest: None,
+        }
+    }
+
+    // Current stochastic RSI value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Compute the latest value from the current candle's RSI.
+    pub fn compute(&mut self, rsi: f64) {
+        if !rsi.is_finite() {
+            return;
+        }
+
+        self.rsi_window.push(rsi);
+        if !self.rsi_window.is_full() {
+            return;
+        }
+
+        if let (Some(min), Some(max)) = (self.rsi_window.min(), self.rsi_window.max()) {
+            // With no range in the window keep the previous value.
+            if max > min {
+                self.latest = Some(100.0 * (rsi - min) / (max - min));
+            } else if self.latest.is_none() {
+                self.latest = Some(0.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rsi() {
+        let mut rsi = Rsi::new(2);
+        rsi.compute(10.0);
+        rsi.compute(12.0);
+        assert!(rsi.latest().is_none());
+
+        // Average gain 1, average loss 0.5.
+        rsi.compute(11.0);
+        assert!((rsi.latest().unwrap() - 100.0 * 2.0 / 3.0).abs() < 1e-9);
+
+        // Wilder smoothed, average gain 0.5, average loss 1.25.
+        rsi.compute(9.0);
+        assert!((rsi.latest().unwrap() - 100.0 * 0.5 / 1.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cutler() {
+        let mut wilder = Rsi::new(2);
+        let mut cutler = Rsi::new_with_smoothing(2, RsiSmoothing::Cutler);
+        for cp in [10.0, 12.0, 11.0].iter() {
+            wilder.compute(*cp);
+            cutler.compute(*cp);
+        }
+
+        // The same until the first average drops out of the window.
+        assert_eq!(cutler.latest(), wilder.latest());
+
+        // Average gain 0, average loss 1.5.
+        wilder.compute(9.0);
+        cutler.compute(9.0);
+        assert_eq!(cutler.latest(), Some(0.0));
+        assert!((wilder.latest().unwrap() - 100.0 * 0.5 / 1.75).abs() < 1e-9);
+
+        // Average gain 1.5, average loss 1.
+        cutler.compute(12.0);
+        assert!((cutler.latest().unwrap() - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn smoothed() {
+        let mut rsi = Rsi::new(5).smoothed(5);
+        assert!(Rsi::new(5).smoothed_latest().is_none());
+
+        let mut raw = Vec::new();
+        let mut smoothed = Vec::new();
+        for i in 0..60 {
+            // Choppy closes, with a sustained drop at the end.
+            let cp = if i < 50 {
+                100.0 + if i % 2 == 0 { 2.0 } else { -1.5 } * (i % 5) as f64
+            } else {
+                100.0 - (i - 49) as f64 * 3.0
+            };
+            rsi.compute(cp);
+            if let (Some(r), Some(s)) = (rsi.latest(), rsi.smoothed_latest()) {
+                raw.push(r);
+                smoothed.push(s);
+            }
+        }
+
+        let variance = |values: &[f64]| {
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+        };
+        let choppy = raw.len() - 10;
+        assert!(variance(&smoothed[..choppy]) < variance(&raw[..choppy]));
+
+        // Lagging the drop, the smoothed RSI stays above the raw RSI.
+        assert!(smoothed.last().unwrap() > raw.last().unwrap());
+    }
+
+    #[test]
+    fn stoch_rsi() {
+        let mut stoch_rsi = StochRsi::new(3);
+        for rsi in [40.0, 60.0].iter() {
+            stoch_rsi.compute(*rsi);
+        }
+        assert!(stoch_rsi.latest().is_none());
+
+        stoch_rsi.compute(50.0);
+        assert_eq!(stoch_rsi.latest(), Some(50.0));
+        stoch_rsi.compute(70.0);
+        assert_eq!(stoch_rsi.latest(), Some(100.0));
+    }
+}
@@ -0,0 +1,227 @@
+// Super smoother (smoother.rs), Ehlers' two pole filter which removes noise
+// shorter than the cutoff period with much less lag than an SMA.
+// John F. Ehlers, Cycle Analytics for Traders, chapter 3.
+// Also Ehlers' Gaussian filter, one to four poles, more poles roll off the noise
+// more steeply.
+// John F. Ehlers, Gaussian and Other Low Lag Filters.
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+#[derive(Debug)]
+pub struct SuperSmoother {
+    // Filter coefficients, from the cutoff period.
+    c1: f64,
+    c2: f64,
+    c3: f64,
+
+    prev_close: Option<f64>,
+    // The previous two filter values, newest first.
+    filt: Option<(f64, f64)>,
+    latest: Option<f64>,
+}
+
+impl SuperSmoother {
+    #[allow(dead_code)]
+    pub fn new(cutoff_period: u16) -> Self {
+        let period = (cutoff_period as f64).max(1.0);
+        let a1 = (-1.414 * PI / period).exp();
+        let b1 = 2.0 * a1 * (1.414 * PI / period).cos();
+        let c2 = b1;
+        let c3 = -a1 * a1;
+
+        SuperSmoother {
+            c1: 1.0 - c2 - c3,
+            c2,
+            c3,
+            prev_close: None,
+            filt: None,
+            latest: None,
+        }
+    }
+
+    // Current filter value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Filter the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        let filt = match (self.prev_close, self.filt) {
+            (Some(prev_close), Some((filt1, filt2))) => {
+                self.c1 * (close_price + prev_close) / 2.0 + self.c2 * filt1 + self.c3 * filt2
+            }
+            // Seed the recursion with the first two closes.
+            _ => close_price,
+        };
+
+        self.filt = self.latest.map(|prev| (filt, prev));
+        self.prev_close = Some(close_price);
+        self.latest = Some(filt);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GaussianFilter {
+    // Weight of the close, and of each previous filter value newest first.
+    gain: f64,
+    coefficients: Vec<f64>,
+
+    // The previous filter values, newest first, one per pole.
+    filt: VecDeque<f64>,
+}
+
+impl GaussianFilter {
+    // Filter with 'poles' (clamped to 1..4) whose response is 3dB down at 'period'.
+    #[allow(dead_code)]
+    pub fn new(poles: u8, period: u16) -> Self {
+        let poles = poles.clamp(1, 4) as i32;
+        let period = (period as f64).max(2.0);
+
+        let beta = (1.0 - (2.0 * PI / period).cos()) / (2f64.powf(1.0 / poles as f64) - 1.0);
+        let alpha = -beta + (beta * beta + 2.0 * beta).sqrt();
+
+        // Binomial expansion of (1 - (1 - alpha)z^-1)^poles.
+        let mut binomial = 1.0;
+        let coefficients = (1..=poles)
+            .map(|k| {
+                binomial = binomial * (poles - k + 1) as f64 / k as f64;
+                let sign = if k % 2 == 1 { 1.0 } else { -1.0 };
+                sign * binomial * (1.0 - alpha).powi(k)
+            })
+            .collect();
+
+        GaussianFilter {
+            gain: alpha.powi(poles),
+            coefficients,
+            filt: VecDeque::with_capacity(poles as usize),
+        }
+    }
+
+    // Current filter value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.filt.front().copied()
+    }
+
+    // Filter the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        // Seed the recursion with the closes until there's a value per pole.
+        let filt = if self.filt.len() < self.coefficients.len() {
+            close_price
+        } else {
+            self.gain * close_price
+                + self
+                    .coefficients
+                    .iter()
+                    .zip(self.filt.iter())
+                    .map(|(c, f)| c * f)
+                    .sum::<f64>()
+        };
+
+        if self.filt.len() == self.coefficients.len() {
+            self.filt.pop_back();
+        }
+        self.filt.push_front(filt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variance(values: &[f64]) -> f64 {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+    }
+
+    #[test]
+    fn gaussian_poles() {
+        // Deterministic noise from an LCG, +/- 1.5 around 100.
+        let mut seed: u64 = 987;
+        let noisy: Vec<f64> = (0..500)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                100.0 + ((seed >> 33) as f64 / (1u64 << 31) as f64 - 0.5) * 3.0
+            })
+            .collect();
+
+        let noise_band = |poles: u8| -> f64 {
+            let mut gf = GaussianFilter::new(poles, 10);
+            let filtered: Vec<f64> = noisy
+                .iter()
+                .map(|cp| {
+                    gf.compute(*cp);
+                    gf.latest().unwrap()
+                })
+                .collect();
+
+            // Settles on the mean.
+            let mean = filtered[100..].iter().sum::<f64>() / 400.0;
+            assert!((mean - 100.0).abs() < 0.5);
+            variance(&filtered[100..])
+        };
+
+        let bands: Vec<f64> = (1..=4).map(noise_band).collect();
+        assert!(bands[0] < variance(&noisy));
+        assert!(bands.windows(2).all(|w| w[1] < w[0]), "{:?}", bands);
+    }
+
+    #[test]
+    fn noisy_sine() {
+        let clean: Vec<f64> = (0..400)
+            .map(|i| 100.0 + 5.0 * (2.0 * PI * i as f64 / 40.0).sin())
+            .collect();
+
+        // Deterministic noise from an LCG, +/- 1.5.
+        let mut seed: u64 = 12345;
+        let noisy: Vec<f64> = clean
+            .iter()
+            .map(|cp| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                cp + ((seed >> 33) as f64 / (1u64 << 31) as f64 - 0.5) * 3.0
+            })
+            .collect();
+
+        let mut ss = SuperSmoother::new(10);
+        let smoothed: Vec<f64> = noisy
+            .iter()
+            .map(|cp| {
+                ss.compute(*cp);
+                ss.latest().unwrap()
+            })
+            .collect();
+
+        // Candle to candle noise is much reduced.
+        let changes =
+            |values: &[f64]| -> Vec<f64> { values[50..].windows(2).map(|w| w[1] - w[0]).collect() };
+        assert!(variance(&changes(&smoothed)) < 0.25 * variance(&changes(&noisy)));
+
+        // The smoothed series best matches the clean one only a few candles back.
+        let lag = (0..10)
+            .min_by(|a, b| {
+                let error = |k: usize| -> f64 {
+                    (50..400)
+                        .map(|i| (smoothed[i] - clean[i - k]).powi(2))
+                        .sum()
+                };
+                error(*a).partial_cmp(&error(*b)).unwrap()
+            })
+            .unwrap();
+        assert!(lag <= 3, "lag {}", lag);
+    }
+}
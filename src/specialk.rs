@@ -0,0 +1,96 @@
+// Pring's Special K (specialk.rs), the KST extended to twelve smoothed rates of
+// change from short to long term, a single momentum line for the primary trend.
+// https://school.stockcharts.com/doku.php?id=technical_indicators:pring_s_special_k
+use crate::kst;
+
+use kst::Kst;
+
+// (ROC period, SMA smoothing period, weight) for each Special K component.
+static SPECIAL_K_COMPONENTS: [(u16, u16, f64); 12] = [
+    (10, 10, 1.0),
+    (15, 10, 2.0),
+    (20, 10, 3.0),
+    (30, 15, 4.0),
+    (40, 50, 1.0),
+    (65, 65, 2.0),
+    (75, 75, 3.0),
+    (100, 100, 4.0),
+    (195, 130, 1.0),
+    (265, 130, 2.0),
+    (390, 130, 3.0),
+    (530, 195, 4.0),
+];
+
+// Number of candles used for the signal line SMA.
+static SPECIAL_K_SIGNAL_PERIOD: u16 = 100;
+
+#[derive(Debug)]
+pub struct SpecialK {
+    kst: Kst,
+}
+
+impl SpecialK {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        SpecialK {
+            kst: Kst::with_components(&SPECIAL_K_COMPONENTS, SPECIAL_K_SIGNAL_PERIOD),
+        }
+    }
+
+    // Current Special K value, None until the longest component has a value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.kst.latest()
+    }
+
+    // Current signal line value, the SMA of the Special K.
+    #[allow(dead_code)]
+    pub fn signal(&self) -> Option<f64> {
+        self.kst.signal()
+    }
+
+    // Compute the latest Special K value based on the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        self.kst.compute(close_price);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ma::MAData;
+    use crate::roc::Roc;
+
+    #[test]
+    fn weighted_components() {
+        let mut special_k = SpecialK::new();
+        let mut components: Vec<(Roc, MAData, f64)> = SPECIAL_K_COMPONENTS
+            .iter()
+            .map(|(roc, sma, weight)| (Roc::new(*roc), MAData::new(*sma), *weight))
+            .collect();
+
+        for i in 0..800 {
+            let cp = 100.0 + 0.05 * i as f64 + 5.0 * (i as f64 / 20.0).sin();
+            special_k.compute(cp);
+
+            let mut expected = Some(0.0);
+            for (roc, sma, weight) in components.iter_mut() {
+                roc.compute(cp);
+                if let Some(r) = roc.latest() {
+                    sma.compute(r, false);
+                }
+                expected = expected.zip(sma.latest()).map(|(sum, s)| sum + s * *weight);
+            }
+
+            match (special_k.latest(), expected) {
+                (Some(k), Some(expected)) => assert!((k - expected).abs() < 1e-9),
+                (k, expected) => assert_eq!(k, expected),
+            }
+        }
+
+        // Ready once the 530 candle ROC has been smoothed over 195 candles.
+        assert!(special_k.latest().is_some());
+        assert!(special_k.signal().is_none());
+    }
+}
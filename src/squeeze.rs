@@ -0,0 +1,128 @@
+// TTM Squeeze (squeeze.rs), volatility contracting until the Bollinger bands sit
+// inside the Keltner channels, with the squeeze firing when they expand back out.
+// The momentum is the linear regression of the close against the midpoint of the
+// recent range and its SMA.
+// https://school.stockcharts.com/doku.php?id=technical_indicators:ttm_squeeze
+use crate::bollinger;
+use crate::keltner;
+use crate::linreg;
+use crate::window;
+
+use bollinger::BollingerBands;
+use keltner::KeltnerChannels;
+use linreg::LinRegMa;
+use window::RollingWindow;
+
+// Standard deviations for the Bollinger bands and ATRs for the Keltner channels.
+static BOLLINGER_STD_DEVS: f64 = 2.0;
+static KELTNER_ATRS: f64 = 1.5;
+
+#[derive(Debug)]
+pub struct TtmSqueeze {
+    pub bollinger: BollingerBands,
+    pub keltner: KeltnerChannels,
+
+    highs: RollingWindow<f64>,
+    lows: RollingWindow<f64>,
+    momentum: LinRegMa,
+
+    squeeze_on: bool,
+    fired: bool,
+}
+
+impl TtmSqueeze {
+    #[allow(dead_code)]
+    pub fn new(period: u16) -> Self {
+        TtmSqueeze {
+            bollinger: BollingerBands::new(period, BOLLINGER_STD_DEVS),
+            keltner: KeltnerChannels::new(period, KELTNER_ATRS, false),
+            highs: RollingWindow::new(period as usize),
+            lows: RollingWindow::new(period as usize),
+            momentum: LinRegMa::new(period),
+            squeeze_on: false,
+            fired: false,
+        }
+    }
+
+    // Whether the Bollinger bands are inside the Keltner channels.
+    #[allow(dead_code)]
+    pub fn squeeze_on(&self) -> bool {
+        self.squeeze_on
+    }
+
+    // Whether the squeeze ended on the latest candle.
+    #[allow(dead_code)]
+    pub fn fired(&self) -> bool {
+        self.fired
+    }
+
+    // Current momentum histogram value, positive when the close is above the
+    // middle of the recent range.
+    #[allow(dead_code)]
+    pub fn momentum(&self) -> Option<f64> {
+        self.momentum.latest()
+    }
+
+    // Compute the latest squeeze from the candle's high, low and close.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, high: f64, low: f64, close: f64) {
+        if !(high.is_finite() && low.is_finite() && close.is_finite()) {
+            return;
+        }
+
+        self.bollinger.compute(close);
+        self.keltner.compute(high, low, close);
+        self.highs.push(high);
+        self.lows.push(low);
+
+        let was_on = self.squeeze_on;
+        self.squeeze_on = match (
+            self.bollinger.upper(),
+            self.bollinger.lower(),
+            self.keltner.upper(),
+            self.keltner.lower(),
+        ) {
+            (Some(bb_upper), Some(bb_lower), Some(kc_upper), Some(kc_lower)) => {
+                bb_upper < kc_upper && bb_lower > kc_lower
+            }
+            _ => false,
+        };
+        self.fired = was_on && !self.squeeze_on;
+
+        if let (Some(highest), Some(lowest), Some(sma)) =
+            (self.highs.max(), self.lows.min(), self.bollinger.middle())
+        {
+            let midpoint = ((highest + lowest) / 2.0 + sma) / 2.0;
+            self.momentum.compute(close - midpoint);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squeeze_fires() {
+        let mut squeeze = TtmSqueeze::new(10);
+
+        // Quiet closes within wide candles.
+        for i in 0..30 {
+            let cp = 100.0 + if i % 2 == 0 { 0.1 } else { -0.1 };
+            squeeze.compute(cp + 1.0, cp - 1.0, cp);
+        }
+        assert!(squeeze.squeeze_on());
+        assert!(!squeeze.fired());
+
+        // Then a breakout, the closes spreading much faster than the ranges grow.
+        let mut fired = false;
+        for i in 1..10 {
+            let cp = 100.0 + 4.0 * i as f64;
+            squeeze.compute(cp + 0.1, cp - 0.1, cp);
+            fired |= squeeze.fired();
+        }
+        assert!(fired);
+        assert!(!squeeze.squeeze_on());
+        assert!(squeeze.momentum().unwrap() > 0.0);
+    }
+}
@@ -0,0 +1,207 @@
+// Schaff Trend Cycle (stc.rs), a double stochastic of the MACD line smoothed into a
+// 0..100 oscillator which turns faster than the MACD itself.
+use crate::ma;
+use crate::position;
+use crate::process_md;
+use crate::tradingpair;
+use crate::window;
+
+use position::PositionType;
+use tradingpair::TradingPair;
+use window::RollingWindow;
+
+use log::{debug, info};
+
+// Number of MACD values the stochastic is computed over.
+static STC_CYCLE_LENGTH: usize = 10;
+
+// Smoothing factor applied to each stochastic.
+static STC_SMOOTHING: f64 = 0.5;
+
+// Signal levels, buy on crossing up through the lower and sell on crossing down
+// through the upper.
+static STC_LOWER: f64 = 25.0;
+static STC_UPPER: f64 = 75.0;
+
+#[derive(Debug, Clone)]
+pub struct Stc {
+    pub macd: ma::MACD,
+
+    macd_window: RollingWindow<f64>,
+    // First stochastic of the MACD, smoothed.
+    smoothed_macd_stoch: Option<f64>,
+    smoothed_window: RollingWindow<f64>,
+
+    latest: Option<f64>,
+    previous: Option<f64>,
+}
+
+// Stochastic of 'value' within the window, None if the window has no range.
+fn stochastic(window: &RollingWindow<f64>, value: f64) -> Option<f64> {
+    let (min, max) = (window.min()?, window.max()?);
+    if max > min {
+        Some(100.0 * (value - min) / (max - min))
+    } else {
+        None
+    }
+}
+
+// Exponentially smooth 'value' onto 'prev', seeding with the value itself.
+fn smooth(prev: Option<f64>, value: f64) -> f64 {
+    match prev {
+        Some(prev) => prev + (STC_SMOOTHING * (value - prev)),
+        None => value,
+    }
+}
+
+impl Stc {
+    pub fn new() -> Self {
+        Stc {
+            macd: ma::MACD::new(),
+            macd_window: RollingWindow::new(STC_CYCLE_LENGTH),
+            smoothed_macd_stoch: None,
+            smoothed_window: RollingWindow::new(STC_CYCLE_LENGTH),
+            latest: None,
+            previous: None,
+        }
+    }
+
+    // Current STC value.
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Previous STC value.
+    pub fn previous(&self) -> Option<f64> {
+        self.previous
+    }
+
+    // Compute the latest STC value based on the close price.
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        self.macd.compute(close_price);
+        let macd = match self.macd.macd_latest {
+            Some(macd) => macd,
+            None => return,
+        };
+
+        self.macd_window.push(macd);
+        if !self.macd_window.is_full() {
+            return;
+        }
+
+        // With no range in the window keep the previous stochastic value.
+        let macd_stoch = stochastic(&self.macd_window, macd)
+            .unwrap_or_else(|| self.smoothed_macd_stoch.unwrap_or(0.0));
+        let smoothed = smooth(self.smoothed_macd_stoch, macd_stoch);
+        self.smoothed_macd_stoch = Some(smoothed);
+
+        self.smoothed_window.push(smoothed);
+        if !self.smoothed_window.is_full() {
+            return;
+        }
+
+        let stoch = stochastic(&self.smoothed_window, smoothed)
+            .unwrap_or_else(|| self.latest.unwrap_or(0.0));
+
+        self.previous = self.latest;
+        self.latest = Some(smooth(self.latest, stoch));
+    }
+}
+
+// STC crossing the 25/75 levels, returns:
+// PositionType::Long if the STC crosses up through 25.
+// PositionType::Short if the STC crosses down through 75.
+pub fn trading_decision_stc(
+    tp: &TradingPair,
+    mt: &process_md::MarketDataTracker,
+    closing_price: f64,
+) -> PositionType {
+    if let (Some(stc), Some(stc_prev)) = (mt.stc.latest(), mt.stc.previous()) {
+        debug!(
+            "[STC] {}, CLOSE: {}, STC: {}, STC_PREV: {}",
+            tp.symbol(),
+            closing_price,
+            stc,
+            stc_prev,
+        );
+
+        if stc > STC_LOWER && stc_prev <= STC_LOWER {
+            info!(
+                "[BUY][STC] {}, close: {}, signal: STC({}) > {} >= STC_PREV({})",
+                tp.symbol(),
+                closing_price,
+                stc,
+                STC_LOWER,
+                stc_prev,
+            );
+
+            return PositionType::Long;
+        } else if stc < STC_UPPER && stc_prev >= STC_UPPER {
+            info!(
+                "[SELL][STC] {}, close: {}, signal: STC({}) < {} <= STC_PREV({})",
+                tp.symbol(),
+                closing_price,
+                stc,
+                STC_UPPER,
+                stc_prev,
+            );
+
+            return PositionType::Short;
+        }
+    }
+
+    PositionType::None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use process_md::{MarketDataTracker, TradeSignal};
+
+    // Closes which fall for 'n' candles then rise for 'n' candles.
+    fn v_shape(n: usize) -> Vec<f64> {
+        let mut closes: Vec<f64> = (0..n).map(|i| 200.0 - i as f64).collect();
+        closes.extend((0..n).map(|i| 200.0 - n as f64 + i as f64));
+        closes
+    }
+
+    #[test]
+    fn range() {
+        let mut stc = Stc::new();
+        for (i, cp) in v_shape(100).iter().enumerate() {
+            stc.compute(cp + (i % 3) as f64);
+            if let Some(v) = stc.latest() {
+                assert!((0.0..=100.0).contains(&v));
+            }
+        }
+        assert!(stc.latest().is_some());
+    }
+
+    #[test]
+    fn leads_macd() {
+        let tp = TradingPair::with_filters("BTC/USDT", 2, 5, 0.00001, 0.01, 10.0);
+        let mut mt = MarketDataTracker::new(TradeSignal::Stc, None, None, true);
+
+        // Compare the STC buy signal against the MACD line turning positive.
+        let mut stc_long = None;
+        let mut macd_positive = None;
+        for (i, cp) in v_shape(60).iter().enumerate() {
+            mt.compute(*cp);
+
+            if stc_long.is_none() && trading_decision_stc(&tp, &mt, *cp) == PositionType::Long {
+                stc_long = Some(i);
+            }
+            if macd_positive.is_none() && mt.stc.macd.macd_latest.unwrap_or(0.0) > 0.0 {
+                macd_positive = Some(i);
+            }
+        }
+
+        assert!(stc_long.unwrap() >= 60);
+        assert!(stc_long.unwrap() < macd_positive.unwrap());
+    }
+}
@@ -0,0 +1,58 @@
+// Close streak (streak.rs), the number of consecutive higher (positive) or lower
+// (negative) closes.
+use crate::prevclose;
+
+use prevclose::PrevClose;
+
+#[derive(Debug, Clone, Default)]
+pub struct CloseStreak {
+    prev_close: PrevClose,
+    streak: i32,
+}
+
+impl CloseStreak {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        CloseStreak::default()
+    }
+
+    // Current streak, 0 after an unchanged close.
+    #[allow(dead_code)]
+    pub fn streak(&self) -> i32 {
+        self.streak
+    }
+
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        if let Some(change) = self.prev_close.update(close_price) {
+            self.streak = if change > 0.0 {
+                self.streak.max(0) + 1
+            } else if change < 0.0 {
+                self.streak.min(0) - 1
+            } else {
+                0
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streak() {
+        let mut streak = CloseStreak::new();
+        let mut streaks = Vec::new();
+        for cp in [1.0, 2.0, 3.0, 2.0, 1.0, 1.0].iter() {
+            streak.compute(*cp);
+            streaks.push(streak.streak());
+        }
+
+        assert_eq!(streaks, vec![0, 1, 2, -1, -2, 0]);
+    }
+}
@@ -22,12 +22,34 @@ pub struct TradingPair {
 
 impl TradingPair {
     pub fn new(bex: &Binance, n: &str) -> TradingPair {
-        let buysell: Vec<&str> = n.split("/").collect();
-        let symbol = String::from(n.replace("/", ""));
+        let symbol = TradingPair::symbol_for(n);
         let lot_size_filter = bex.get_lot_size_filter(&symbol).unwrap();
         let price_filter = bex.get_price_filter(&symbol).unwrap();
         let min_notional = bex.get_min_notional_filter(&symbol).unwrap();
 
+        TradingPair::with_filters(
+            n,
+            price_filter.decimal_places,
+            lot_size_filter.decimal_places,
+            lot_size_filter.min_qty,
+            price_filter.tick_size,
+            min_notional,
+        )
+    }
+
+    // Create a trading pair from already known exchange filters, without
+    // querying the exchange.
+    pub fn with_filters(
+        n: &str,
+        price_dps: i8,
+        qty_dps: i8,
+        min_order: f64,
+        tick_size: f64,
+        min_notional: f64,
+    ) -> TradingPair {
+        let buysell: Vec<&str> = n.split("/").collect();
+        let symbol = TradingPair::symbol_for(n);
+
         TradingPair {
             // EXAMPLE.
             name: String::from(n),                   // BTC/USDT.
@@ -44,14 +66,19 @@ impl TradingPair {
                 None
             },
 
-            qty_dps: lot_size_filter.decimal_places,
-            price_dps: price_filter.decimal_places,
-            min_order: lot_size_filter.min_qty,
-            tick_size: price_filter.tick_size,
-            min_notional: min_notional,
+            qty_dps,
+            price_dps,
+            min_order,
+            tick_size,
+            min_notional,
         }
     }
 
+    // Exchange symbol for a pair name, BTC/USDT is BTCUSDT.
+    fn symbol_for(n: &str) -> String {
+        n.replace("/", "")
+    }
+
     pub fn get_bvlt_type(&self) -> &Option<BvltType> {
         &self.bvlt_type
     }
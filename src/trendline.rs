@@ -0,0 +1,168 @@
+// Trend line breaks (trendline.rs), a line fitted through recent swing lows is
+// support and one through swing highs resistance, the latest close moving through
+// either is a break.
+use crate::candlestick;
+use crate::position;
+
+use candlestick::Candle;
+use position::PositionType;
+
+// Candles either side of a swing high or low which must be lower or higher.
+static SWING_CANDLES: usize = 1;
+
+// Swing points within this percentage of the fitted line touch it.
+static TOUCH_TOLERANCE_PCT: f64 = 0.5;
+
+// Touches required for a line to count, by default.
+static DEFAULT_MIN_TOUCHES: usize = 3;
+
+// Least squares line through the points, (slope, intercept).
+fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let var_x: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if var_x == 0.0 {
+        return None;
+    }
+
+    let cov: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let slope = cov / var_x;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+// Fit a line through the swing points of 'prices', returning its value at
+// 'at' if enough of the swing points touch it. 'lower' picks swing lows.
+fn line_at(prices: &[f64], lower: bool, min_touches: usize, at: usize) -> Option<f64> {
+    let is_swing = |i: usize| {
+        (1..=SWING_CANDLES).all(|k| {
+            if lower {
+                prices[i] < prices[i - k] && prices[i] < prices[i + k]
+            } else {
+                prices[i] > prices[i - k] && prices[i] > prices[i + k]
+            }
+        })
+    };
+
+    let swings: Vec<(f64, f64)> = (SWING_CANDLES..prices.len().saturating_sub(SWING_CANDLES))
+        .filter(|i| is_swing(*i))
+        .map(|i| (i as f64, prices[i]))
+        .collect();
+    if swings.len() < min_touches.max(2) {
+        return None;
+    }
+
+    let (slope, intercept) = fit_line(&swings)?;
+    let touches = swings
+        .iter()
+        .filter(|(x, y)| {
+            let line = slope * x + intercept;
+            (y - line).abs() <= line.abs() * TOUCH_TOLERANCE_PCT / 100.0
+        })
+        .count();
+
+    if touches >= min_touches {
+        Some(slope * at as f64 + intercept)
+    } else {
+        None
+    }
+}
+
+// Detects the latest candle closing through a trend line, returns:
+// PositionType::Long if it closes above resistance.
+// PositionType::Short if it closes below support.
+// Uses the default minimum touch count.
+#[allow(dead_code)]
+pub fn detect_trendline_break(candles: &[Candle]) -> Option<PositionType> {
+    detect_trendline_break_with(candles, DEFAULT_MIN_TOUCHES)
+}
+
+// As detect_trendline_break(), a line needs 'min_touches' swing points on it. The
+// lines are fitted to every candle before the latest, and the candle before the
+// latest must have closed on the near side of the line.
+pub fn detect_trendline_break_with(candles: &[Candle], min_touches: usize) -> Option<PositionType> {
+    if candles.len() < 2 * SWING_CANDLES + 3 {
+        return None;
+    }
+
+    let last = candles.len() - 1;
+    let (latest, prev) = (&candles[last], &candles[last - 1]);
+    let history = &candles[..last];
+
+    let lows: Vec<f64> = history.iter().map(|c| c.low).collect();
+    if let (Some(support), Some(prev_support)) = (
+        line_at(&lows, true, min_touches, last),
+        line_at(&lows, true, min_touches, last - 1),
+    ) {
+        if latest.close < support && prev.close >= prev_support {
+            return Some(PositionType::Short);
+        }
+    }
+
+    let highs: Vec<f64> = history.iter().map(|c| c.high).collect();
+    if let (Some(resistance), Some(prev_resistance)) = (
+        line_at(&highs, false, min_touches, last),
+        line_at(&highs, false, min_touches, last - 1),
+    ) {
+        if latest.close > resistance && prev.close <= prev_resistance {
+            return Some(PositionType::Long);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(close: f64) -> Candle {
+        Candle {
+            open_time: 0,
+            close_time: 0,
+            open: close,
+            high: close + 0.25,
+            low: close - 0.25,
+            close,
+            volume: 0.0,
+            symbol: None,
+        }
+    }
+
+    // Rising zig-zag with swing lows every 4 candles on a line of slope 0.5.
+    fn ascending() -> Vec<Candle> {
+        let offsets = [2.0, 1.0, 0.0, 1.0];
+        (0..17)
+            .map(|i| candle(100.0 + 0.5 * i as f64 + offsets[i % 4]))
+            .collect()
+    }
+
+    #[test]
+    fn support_break() {
+        let mut candles = ascending();
+        candles.push(candle(109.0));
+        assert_eq!(detect_trendline_break(&candles), None);
+
+        candles.pop();
+        candles.push(candle(100.0));
+        assert_eq!(detect_trendline_break(&candles), Some(PositionType::Short));
+
+        // Only four swing lows touch the line.
+        assert_eq!(
+            detect_trendline_break_with(&candles, 4),
+            Some(PositionType::Short)
+        );
+        assert_eq!(detect_trendline_break_with(&candles, 5), None);
+    }
+
+    #[test]
+    fn resistance_break() {
+        let mut candles = ascending();
+        candles.push(candle(120.0));
+        assert_eq!(detect_trendline_break(&candles), Some(PositionType::Long));
+    }
+}
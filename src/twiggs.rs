@@ -0,0 +1,115 @@
+// Twiggs Money Flow (twiggs.rs), Chaikin Money Flow using the true range so gaps
+// between candles count, and Wilder smoothing instead of a plain sum.
+// https://www.incrediblecharts.com/indicators/twiggs_money_flow.php
+use crate::prevclose;
+
+use prevclose::PrevClose;
+
+#[derive(Debug, Clone)]
+pub struct TwiggsMoneyFlow {
+    // Number of candles used for Wilder smoothing.
+    pub period: u16,
+
+    prev_close: PrevClose,
+
+    // Wilder smoothed money flow volume and volume, seeded with plain averages.
+    flow_smoothed: f64,
+    volume_smoothed: f64,
+    num_candles: u16,
+
+    latest: Option<f64>,
+}
+
+impl TwiggsMoneyFlow {
+    #[allow(dead_code)]
+    pub fn new(period: u16) -> Self {
+        TwiggsMoneyFlow {
+            period,
+            prev_close: PrevClose::new(),
+            flow_smoothed: 0.0,
+            volume_smoothed: 0.0,
+            num_candles: 0,
+            latest: None,
+        }
+    }
+
+    // Current money flow, between -1 (distribution) and 1 (accumulation).
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Add a candle, the first only provides the previous close for the true range.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, high: f64, low: f64, close: f64, volume: f64) {
+        if self.period == 0
+            || !(high.is_finite() && low.is_finite() && close.is_finite() && volume.is_finite())
+        {
+            return;
+        }
+
+        let prev_close = match self.prev_close.prev() {
+            Some(prev_close) => prev_close,
+            None => {
+                self.prev_close.update(close);
+                return;
+            }
+        };
+        self.prev_close.update(close);
+
+        let true_high = high.max(prev_close);
+        let true_low = low.min(prev_close);
+        let true_range = true_high - true_low;
+        let flow = if true_range > 0.0 {
+            volume * ((close - true_low) - (true_high - close)) / true_range
+        } else {
+            0.0
+        };
+
+        let period = self.period as f64;
+        if self.num_candles < self.period {
+            self.num_candles += 1;
+            let n = self.num_candles as f64;
+            self.flow_smoothed += (flow - self.flow_smoothed) / n;
+            self.volume_smoothed += (volume - self.volume_smoothed) / n;
+            if self.num_candles < self.period {
+                return;
+            }
+        } else {
+            self.flow_smoothed += (flow - self.flow_smoothed) / period;
+            self.volume_smoothed += (volume - self.volume_smoothed) / period;
+        }
+
+        self.latest = if self.volume_smoothed > 0.0 {
+            Some(self.flow_smoothed / self.volume_smoothed)
+        } else {
+            Some(0.0)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulation() {
+        let mut tmf = TwiggsMoneyFlow::new(5);
+        tmf.compute(101.0, 99.0, 100.0, 1000.0);
+        for i in 1..5 {
+            // Closing near the high each candle.
+            let cp = 100.0 + i as f64;
+            tmf.compute(cp + 0.2, cp - 1.0, cp, 1000.0);
+            assert!(tmf.latest().is_none());
+        }
+
+        let cp = 105.0;
+        tmf.compute(cp + 0.2, cp - 1.0, cp, 1000.0);
+        let flow = tmf.latest().unwrap();
+        assert!(flow > 0.5 && flow <= 1.0);
+
+        // A gap down closing mid-range counts as distribution.
+        tmf.compute(95.0, 90.0, 92.5, 5000.0);
+        assert!(tmf.latest().unwrap() < flow);
+    }
+}
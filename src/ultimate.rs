@@ -0,0 +1,112 @@
+// Ultimate Oscillator (ultimate.rs), buying pressure averaged over three periods
+// and weighted towards the shortest, 0..100. Mixing the periods cuts down the
+// false divergences a single period oscillator gives.
+// https://www.investopedia.com/terms/u/ultimateoscillator.asp
+use crate::window;
+
+use window::RollingWindow;
+
+// (period, weight) of each average, shortest first.
+static ULTIMATE_PERIODS: [(usize, f64); 3] = [(7, 4.0), (14, 2.0), (28, 1.0)];
+
+#[derive(Debug, Clone)]
+pub struct UltimateOscillator {
+    prev_close: Option<f64>,
+
+    // Buying pressure and true range of the candles covering the longest period.
+    buying_pressure: RollingWindow<f64>,
+    true_range: RollingWindow<f64>,
+
+    latest: Option<f64>,
+}
+
+impl UltimateOscillator {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        let longest = ULTIMATE_PERIODS[ULTIMATE_PERIODS.len() - 1].0;
+        UltimateOscillator {
+            prev_close: None,
+            buying_pressure: RollingWindow::new(longest),
+            true_range: RollingWindow::new(longest),
+            latest: None,
+        }
+    }
+
+    // Current Ultimate Oscillator value.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Compute the latest value from the candle's high, low and close.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, high: f64, low: f64, close: f64) {
+        if !(high.is_finite() && low.is_finite() && close.is_finite()) {
+            return;
+        }
+
+        if let Some(prev_close) = self.prev_close {
+            let true_low = low.min(prev_close);
+            self.buying_pressure.push(close - true_low);
+            self.true_range.push(high.max(prev_close) - true_low);
+        }
+        self.prev_close = Some(close);
+
+        if !self.true_range.is_full() {
+            return;
+        }
+
+        let mut weighted = 0.0;
+        let mut weights = 0.0;
+        for (period, weight) in ULTIMATE_PERIODS.iter() {
+            let bp: f64 = self.buying_pressure.iter().take(*period).sum();
+            let tr: f64 = self.true_range.iter().take(*period).sum();
+            if tr > 0.0 {
+                weighted += weight * bp / tr;
+            } else {
+                weighted += weight * 0.5;
+            }
+            weights += weight;
+        }
+
+        self.latest = Some(100.0 * weighted / weights);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighting() {
+        let mut uo = UltimateOscillator::new();
+        // Closing on the low, no buying pressure.
+        for _ in 0..22 {
+            uo.compute(101.0, 100.0, 100.0);
+        }
+        assert!(uo.latest().is_none());
+
+        // Then the last 7 close on the high, so the averages are 1, 0.5 and 0.25.
+        for _ in 0..7 {
+            uo.compute(101.0, 100.0, 101.0);
+        }
+        let expected = 100.0 * (4.0 * 1.0 + 2.0 * 0.5 + 0.25) / 7.0;
+        assert!((uo.latest().unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bounded() {
+        let mut uo = UltimateOscillator::new();
+        let mut cp = 100.0;
+        for i in 0..200 {
+            cp += ((i * 37) % 11) as f64 - 5.0;
+            let (high, low) = (cp + (i % 3) as f64, cp - (i % 4) as f64);
+            uo.compute(high, low, cp);
+
+            if let Some(latest) = uo.latest() {
+                assert!((0.0..=100.0).contains(&latest));
+            }
+        }
+        assert!(uo.latest().is_some());
+    }
+}
@@ -0,0 +1,82 @@
+// Volume spike (volume.rs), flags candles trading far more than the recent
+// average volume, which confirms breakouts.
+use crate::window;
+
+use window::RollingWindow;
+
+#[derive(Debug, Clone)]
+pub struct VolumeSpike {
+    // Number of previous candles averaged over.
+    #[allow(dead_code)]
+    pub window: usize,
+    // Times the average volume a candle needs to be a spike.
+    pub multiplier: f64,
+
+    volumes: RollingWindow<f64>,
+    ratio: Option<f64>,
+}
+
+impl VolumeSpike {
+    #[allow(dead_code)]
+    pub fn new(window: usize, multiplier: f64) -> Self {
+        VolumeSpike {
+            window,
+            multiplier,
+            volumes: RollingWindow::new(window),
+            ratio: None,
+        }
+    }
+
+    // Latest candle's volume over the average of the window of candles before it.
+    #[allow(dead_code)]
+    pub fn ratio(&self) -> Option<f64> {
+        self.ratio
+    }
+
+    #[allow(dead_code)]
+    pub fn is_spike(&self) -> bool {
+        self.ratio.is_some_and(|ratio| ratio > self.multiplier)
+    }
+
+    #[allow(dead_code)]
+    pub fn compute(&mut self, volume: f64) {
+        if !volume.is_finite() {
+            return;
+        }
+
+        self.ratio = if self.volumes.is_full() {
+            let average = self.volumes.sum() / self.volumes.len() as f64;
+            if average > 0.0 {
+                Some(volume / average)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        self.volumes.push(volume);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spike() {
+        let mut vs = VolumeSpike::new(10, 3.0);
+        for _ in 0..10 {
+            vs.compute(100.0);
+            assert!(!vs.is_spike());
+        }
+
+        vs.compute(110.0);
+        assert!((vs.ratio().unwrap() - 1.1).abs() < 1e-9);
+        assert!(!vs.is_spike());
+
+        vs.compute(500.0);
+        assert!((vs.ratio().unwrap() - 500.0 / 101.0).abs() < 1e-9);
+        assert!(vs.is_spike());
+    }
+}
@@ -0,0 +1,88 @@
+// Anchored VWAP (vwap.rs), the volume weighted average typical price of every
+// candle since a chosen anchor candle, e.g. a significant swing low.
+// https://www.investopedia.com/terms/v/vwap.asp
+
+#[derive(Debug, Clone)]
+pub struct AnchoredVwap {
+    // Index of the candle to start from, counting from 0 at the first computed.
+    pub anchor_index: u64,
+    candles_seen: u64,
+
+    // Running totals since the anchor.
+    price_volume: f64,
+    volume: f64,
+}
+
+impl AnchoredVwap {
+    #[allow(dead_code)]
+    pub fn new(anchor_index: u64) -> Self {
+        AnchoredVwap {
+            anchor_index,
+            candles_seen: 0,
+            price_volume: 0.0,
+            volume: 0.0,
+        }
+    }
+
+    // Current VWAP, None before the anchor or while no volume has traded since.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        if self.volume > 0.0 {
+            Some(self.price_volume / self.volume)
+        } else {
+            None
+        }
+    }
+
+    // Add the candle, counting it once the anchor candle is reached.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, high: f64, low: f64, close: f64, volume: f64) {
+        if ![high, low, close, volume].iter().all(|v| v.is_finite()) {
+            return;
+        }
+
+        let index = self.candles_seen;
+        self.candles_seen += 1;
+        if index < self.anchor_index {
+            return;
+        }
+
+        let typical_price = (high + low + close) / 3.0;
+        self.price_volume += typical_price * volume;
+        self.volume += volume;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchored_at_low() {
+        // (high, low, close, volume), the low is the third candle.
+        let candles = [
+            (105.0, 103.0, 104.0, 10.0),
+            (104.0, 100.0, 101.0, 20.0),
+            (101.0, 95.0, 97.0, 30.0),
+            (103.0, 98.0, 102.0, 10.0),
+            (108.0, 102.0, 107.0, 20.0),
+        ];
+
+        let mut vwap = AnchoredVwap::new(2);
+        for (i, (h, l, c, v)) in candles.iter().enumerate() {
+            vwap.compute(*h, *l, *c, *v);
+            match i {
+                0 | 1 => assert_eq!(vwap.latest(), None),
+                2 => assert_eq!(vwap.latest(), Some((101.0 + 95.0 + 97.0) / 3.0)),
+                _ => {}
+            }
+        }
+
+        let tp = |(h, l, c, _): (f64, f64, f64, f64)| (h + l + c) / 3.0;
+        let expected =
+            (tp(candles[2]) * 30.0 + tp(candles[3]) * 10.0 + tp(candles[4]) * 20.0) / 60.0;
+        assert!((vwap.latest().unwrap() - expected).abs() < 1e-9);
+        // Above the anchor low as price recovers.
+        assert!(vwap.latest().unwrap() > tp(candles[2]));
+    }
+}
@@ -0,0 +1,260 @@
+// Fixed size rolling window of values (window.rs), shared by the indicators.
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+
+#[derive(Debug, Clone)]
+pub struct RollingWindow<T> {
+    // Values in the window, newest at the front.
+    buf: VecDeque<T>,
+    // Maximum number of values held.
+    cap: usize,
+
+    // Running sum of the values in the window.
+    sum: T,
+    // Pushes since the running sum was last recomputed from scratch.
+    pushes_since_resync: usize,
+
+    // Monotonic (index, value) queues, front holds the current min/max.
+    min_queue: VecDeque<(u64, T)>,
+    max_queue: VecDeque<(u64, T)>,
+    // Index the next pushed value will get.
+    next_index: u64,
+}
+
+impl<T> RollingWindow<T>
+where
+    T: Copy + Default + PartialOrd + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn new(cap: usize) -> Self {
+        RollingWindow {
+            buf: VecDeque::with_capacity(cap),
+            cap,
+            sum: T::default(),
+            pushes_since_resync: 0,
+            min_queue: VecDeque::with_capacity(cap),
+            max_queue: VecDeque::with_capacity(cap),
+            next_index: 0,
+        }
+    }
+
+    // Add the newest value, evicting and returning the oldest if the window is full.
+    pub fn push(&mut self, value: T) -> Option<T> {
+        if self.cap == 0 {
+            return Some(value);
+        }
+
+        let evicted = if self.buf.len() == self.cap {
+            let oldest_index = self.oldest_index();
+            if self.min_queue.front().map(|(i, _)| *i) == Some(oldest_index) {
+                self.min_queue.pop_front();
+            }
+            if self.max_queue.front().map(|(i, _)| *i) == Some(oldest_index) {
+                self.max_queue.pop_front();
+            }
+
+            let oldest = self.buf.pop_back();
+            if let Some(oldest) = oldest {
+                self.sum = self.sum - oldest;
+            }
+            oldest
+        } else {
+            None
+        };
+
+        self.buf.push_front(value);
+        self.sum = self.sum + value;
+        self.enqueue(self.next_index, value);
+        self.next_index += 1;
+
+        // Floating point values drift when repeatedly added and subtracted, so
+        // recompute the sum once per window length.
+        self.pushes_since_resync += 1;
+        if self.pushes_since_resync >= self.cap {
+            self.resync_sum();
+        }
+
+        evicted
+    }
+
+    // Remove and return the newest value. This rebuilds the min/max aggregates so
+    // is O(n), it's intended for corrections rather than the normal data path.
+    pub fn pop_newest(&mut self) -> Option<T> {
+        let newest = self.buf.pop_front()?;
+        self.next_index -= 1;
+        self.sum = self.sum - newest;
+        self.rebuild_queues();
+        Some(newest)
+    }
+
+    // Put back a value as the oldest in the window, the reverse of an eviction.
+    // Ignored if the window is full. O(n), see pop_newest().
+    pub fn push_oldest(&mut self, value: T) {
+        if self.buf.len() == self.cap {
+            return;
+        }
+
+        self.buf.push_back(value);
+        self.sum = self.sum + value;
+        self.rebuild_queues();
+    }
+
+    // Number of values currently held.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    // Maximum number of values held.
+    #[allow(dead_code)]
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    // Number of values the window can hold without reallocating, at least cap().
+    #[allow(dead_code)]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.cap > 0 && self.buf.len() == self.cap
+    }
+
+    // Iterate over the values, newest first.
+    pub fn iter(&self) -> std::collections::vec_deque::Iter<'_, T> {
+        self.buf.iter()
+    }
+
+    // Newest value.
+    #[allow(dead_code)]
+    pub fn newest(&self) -> Option<T> {
+        self.buf.front().copied()
+    }
+
+    // Oldest value.
+    pub fn oldest(&self) -> Option<T> {
+        self.buf.back().copied()
+    }
+
+    // Sum of the values in the window.
+    pub fn sum(&self) -> T {
+        self.sum
+    }
+
+    // Smallest value in the window.
+    #[allow(dead_code)]
+    pub fn min(&self) -> Option<T> {
+        self.min_queue.front().map(|(_, v)| *v)
+    }
+
+    // Largest value in the window.
+    #[allow(dead_code)]
+    pub fn max(&self) -> Option<T> {
+        self.max_queue.front().map(|(_, v)| *v)
+    }
+
+    // Index of the oldest value held.
+    fn oldest_index(&self) -> u64 {
+        self.next_index - self.buf.len() as u64
+    }
+
+    fn enqueue(&mut self, index: u64, value: T) {
+        // Values which can no longer be the min/max are dropped.
+        while let Some((_, v)) = self.min_queue.back() {
+            if *v < value {
+                break;
+            }
+            self.min_queue.pop_back();
+        }
+        self.min_queue.push_back((index, value));
+
+        while let Some((_, v)) = self.max_queue.back() {
+            if *v > value {
+                break;
+            }
+            self.max_queue.pop_back();
+        }
+        self.max_queue.push_back((index, value));
+    }
+
+    fn resync_sum(&mut self) {
+        self.sum = self.buf.iter().fold(T::default(), |acc, v| acc + *v);
+        self.pushes_since_resync = 0;
+    }
+
+    fn rebuild_queues(&mut self) {
+        self.min_queue.clear();
+        self.max_queue.clear();
+
+        let oldest_index = self.oldest_index();
+        let values: Vec<T> = self.buf.iter().rev().copied().collect();
+        for (i, value) in values.into_iter().enumerate() {
+            self.enqueue(oldest_index + i as u64, value);
+        }
+    }
+}
+
+// Windows are equal if they hold the same values, regardless of how they got there.
+impl<T: PartialEq> PartialEq for RollingWindow<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cap == other.cap && self.buf == other.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates() {
+        let mut window = RollingWindow::new(5);
+        let values: Vec<f64> = (0..200)
+            .map(|i| ((i * 37) % 23) as f64 - 11.0 + (i as f64 * 0.1))
+            .collect();
+
+        for (i, v) in values.iter().enumerate() {
+            let evicted = window.push(*v);
+            if i >= 5 {
+                assert_eq!(evicted, Some(values[i - 5]));
+            } else {
+                assert_eq!(evicted, None);
+            }
+
+            let start = i.saturating_sub(4);
+            let expected = &values[start..=i];
+            let sum: f64 = expected.iter().sum();
+            let min = expected.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = expected.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+            assert!((window.sum() - sum).abs() < 1e-9);
+            assert_eq!(window.min(), Some(min));
+            assert_eq!(window.max(), Some(max));
+            assert_eq!(window.is_full(), i >= 4);
+            assert_eq!(window.newest(), Some(*v));
+            assert_eq!(window.oldest(), Some(values[start]));
+        }
+    }
+
+    #[test]
+    fn pop_newest() {
+        let mut window = RollingWindow::new(3);
+        for v in [5, 1, 3, 9].iter() {
+            window.push(*v);
+        }
+
+        assert_eq!(window.pop_newest(), Some(9));
+        window.push_oldest(5);
+        assert_eq!(window.iter().copied().collect::<Vec<i64>>(), vec![3, 1, 5]);
+        assert_eq!(window.sum(), 9);
+        assert_eq!(window.min(), Some(1));
+        assert_eq!(window.max(), Some(5));
+
+        window.push(0);
+        assert_eq!(window.min(), Some(0));
+        assert_eq!(window.max(), Some(3));
+    }
+}
@@ -0,0 +1,68 @@
+// Z-score oscillator (zscore.rs), standard deviations of the close from its SMA.
+// Beyond +/-2 the close is stretched and a mean reversion is more likely.
+use crate::ma;
+
+use ma::MAData;
+
+#[derive(Debug, Clone)]
+pub struct ZScore {
+    pub ma: MAData,
+    latest: Option<f64>,
+}
+
+impl ZScore {
+    #[allow(dead_code)]
+    pub fn new(period: u16) -> Self {
+        ZScore {
+            ma: MAData::new(period),
+            latest: None,
+        }
+    }
+
+    // Current z-score, None until the window is full.
+    #[allow(dead_code)]
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+    }
+
+    // Compute the latest z-score based on the close price.
+    #[allow(dead_code)]
+    pub fn compute(&mut self, close_price: f64) {
+        if !close_price.is_finite() {
+            return;
+        }
+
+        self.ma.compute(close_price, false);
+        if let (Some(mean), Some(std_dev)) = (self.ma.latest(), self.ma.std_dev()) {
+            // Flat closes aren't stretched in either direction.
+            self.latest = if std_dev > 0.0 {
+                Some((close_price - mean) / std_dev)
+            } else {
+                Some(0.0)
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_std_devs() {
+        let mut z = ZScore::new(5);
+        for _ in 0..4 {
+            z.compute(10.0);
+        }
+        assert!(z.latest().is_none());
+
+        // Mean 11, population std dev 2.
+        z.compute(15.0);
+        assert!((z.latest().unwrap() - 2.0).abs() < 1e-9);
+
+        for _ in 0..5 {
+            z.compute(15.0);
+        }
+        assert_eq!(z.latest(), Some(0.0));
+    }
+}